@@ -1,46 +1,212 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use log::debug;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tauri::State;
 use tokio::sync::RwLock;
 
 use crate::{
     api::manage::Workers,
-    funcs::{self, client, utils},
-    target::exchange::{get_rest_instruments, get_rest_ticker_info, ExchangeName},
+    funcs::{
+        self, balance, client, kill, pnl, risk,
+        task::{RunnerContext, STOP_CANCEL_TIMEOUT},
+        utils,
+    },
+    target::{
+        exchange::{self, get_rest_instruments, get_rest_ticker_info, ExchangeName},
+        exchanges::models::Position,
+        order::OrderInfo,
+    },
 };
 
 pub struct AppState {
+    // Runner IDごとの設定及び実行状態
+    pub runners: HashMap<String, Runner>,
+
+    // 当日の確定損益、Runnerの再起動をまたいで保持する
+    // 口座全体の損失の上限に使用するため、全てのRunnerで共有する
+    pub risk_ledger: Arc<RwLock<risk::Ledger>>,
+}
+
+// 1つの銘柄を追従するRunnerの設定及び実行状態
+#[derive(Default)]
+pub struct Runner {
     pub controller: client::Controller,
 
     pub workers: Option<Workers>,
 
     pub logger: Option<Arc<RwLock<client::Logger>>>,
+
+    // 起動処理中、Workerの設定までの間も同じ口座・銘柄の起動を受け付けない
+    pub is_starting: bool,
 }
 
-#[tauri::command]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RunnerStatus {
+    #[serde(rename = "stopped")]
+    Stopped,
+    #[serde(rename = "starting")]
+    Starting,
+    #[serde(rename = "running")]
+    Running,
+    // 全ての側が完了または停止したが、停止の操作を行っていない
+    #[serde(rename = "finished")]
+    Finished,
+}
+
+impl Runner {
+    pub fn status(&self) -> RunnerStatus {
+        if self.is_starting {
+            return RunnerStatus::Starting;
+        }
+        match self.workers.as_ref().and_then(|w| w.context.as_ref()) {
+            None => RunnerStatus::Stopped,
+            Some(context) if context.cancel_handle.is_cancelled() => RunnerStatus::Finished,
+            Some(_) => RunnerStatus::Running,
+        }
+    }
+}
+
+// Runner一覧の要素
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunnerItem {
+    pub runner_id: String,
+    pub status: RunnerStatus,
+    pub controller: client::Controller,
+    // 起動中または完了したRunnerの損益及び手数料
+    pub pnl: Option<pnl::Summary>,
+}
+
+// 停止時の最終状態
+// Controllerの各値はそのまま展開し、停止前の画面側の型と互換を保つ
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopResponse {
+    #[serde(flatten)]
+    pub controller: client::Controller,
+    // 各側の注文状態、先頭が主側
+    pub orders: Vec<OrderInfo>,
+    pub positions: Vec<Position>,
+    // 起動中の損益及び手数料
+    pub pnl: Option<pnl::Summary>,
+}
+
+// 全停止の結果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillResponse {
+    // 停止したRunner
+    pub runner_ids: Vec<String>,
+    // 銘柄ごとのキャンセル及び決済の結果
+    pub results: Vec<kill::KillResult>,
+}
+
+fn runner_not_found(runner_id: &str) -> Value {
+    utils::err_response_handler(
+        "runner is not found, please post controller",
+        &format!("runner id: {}", runner_id),
+    )
+}
+
+#[tauri::command(rename_all = "snake_case")]
 pub async fn start_controller(
     state: State<'_, Arc<RwLock<AppState>>>,
+    runner_id: String,
 ) -> Result<client::Controller, Value> {
-    let (cloned_controller, cloned_logger) = {
+    start_runner(state.inner(), &runner_id).await
+}
+
+#[tauri::command(rename_all = "snake_case")]
+pub async fn stop_controller(
+    state: State<'_, Arc<RwLock<AppState>>>,
+    runner_id: String,
+) -> Result<StopResponse, Value> {
+    stop_runner(state.inner(), &runner_id).await
+}
+
+// Runnerを起動する
+// 画面からの起動及び取引時間帯による自動起動で共有する
+// 同じRunner IDのみ再起動し、他のRunnerには影響しない
+pub async fn start_runner(
+    state: &Arc<RwLock<AppState>>,
+    runner_id: &str,
+) -> Result<client::Controller, Value> {
+    let controller = match state.read().await.runners.get(runner_id) {
+        Some(runner) => runner.controller.clone(),
+        None => return Err(runner_not_found(runner_id)),
+    };
+    if let Err(e) = controller.ok() {
+        return Err(utils::err_response_handler(
+            "controller is not ok, please check value",
+            e,
+        ));
+    }
+
+    // 残高の確認は取引所へ問い合わせるため、ロックを保持せずに行う
+    // テストの場合は発注しないため確認しない
+    let is_test = std::env::var("IS_TEST").unwrap_or_default() == "true";
+    if !is_test {
+        if let Err(e) = balance::verify(&controller).await {
+            return Err(utils::err_response_handler(
+                "balance check failed, please check balance",
+                &e,
+            ));
+        }
+    }
+
+    // すでに実行してるWorkerがあれば取り出し、ロックを解放してから停止する
+    // why: 注文のキャンセルを待つ間、他のRunnerの操作及び状態の参照を止めない
+    let previous = {
         let mut w = state.write().await;
-        match w.controller.ok() {
-            Ok(_) => (),
-            Err(e) => {
-                return Err(utils::err_response_handler(
-                    "controller is not ok, please check value",
-                    e,
-                ));
-            }
+        // 同じ口座・銘柄で起動中の他のRunnerがあれば起動しない
+        // 前回起動時の注文の整理は自己注文IDの接頭辞で行うため、他のRunnerの注文もキャンセルされる
+        if let Some((other_id, _)) = w.runners.iter().find(|(id, runner)| {
+            id.as_str() != runner_id
+                && matches!(
+                    runner.status(),
+                    RunnerStatus::Starting | RunnerStatus::Running
+                )
+                && runner.controller.is_same_target(&controller)
+        }) {
+            return Err(utils::err_response_handler(
+                "same symbol is running on the same account, please stop it",
+                &format!("runner id: {}", other_id),
+            ));
         }
 
-        // すでに実行してるWorkerがあれば停止
-        if let Some(mut workers) = w.workers.take() {
-            debug!("workers[{}] is done, abort_all", workers.handles.len());
-            workers.abort_all().await.unwrap();
-            w.workers = None;
+        let runner = match w.runners.get_mut(runner_id) {
+            Some(v) => v,
+            None => return Err(runner_not_found(runner_id)),
+        };
+        if runner.is_starting {
+            return Err(utils::err_response_handler(
+                "runner is starting, please wait",
+                &format!("runner id: {}", runner_id),
+            ));
         }
+        // why: Workerの設定はロックを解放して起動した後のため、確認と同じロックの中で対象を予約する
+        runner.is_starting = true;
+        runner.workers.take()
+    };
+    // 注文をキャンセルしてから停止する
+    if let Some(mut workers) = previous {
+        debug!("workers[{}] is done, shutdown", workers.handles.len());
+        if let Err(e) = workers.shutdown(STOP_CANCEL_TIMEOUT * 2).await {
+            release_starting(state, runner_id).await;
+            return Err(utils::err_response_handler(
+                "shutdown is failed, workers is not found",
+                &e.to_string(),
+            ));
+        }
+    }
+
+    let (cloned_controller, cloned_logger, cloned_risk_ledger) = {
+        let mut w = state.write().await;
+        let risk_ledger = w.risk_ledger.clone();
+        let runner = match w.runners.get_mut(runner_id) {
+            Some(v) => v,
+            None => return Err(runner_not_found(runner_id)),
+        };
 
         let set_log = Some(client::Log {
             level: "info".to_string(),
@@ -48,38 +214,71 @@ pub async fn start_controller(
             timestamp: chrono::Local::now().to_rfc3339(),
         });
         let logger = Arc::new(RwLock::new(client::Logger::new(set_log.clone())));
-        w.logger = Some(logger.clone());
+        runner.logger = Some(logger.clone());
 
-        (Arc::new(RwLock::new(w.controller.clone())), logger.clone())
+        (
+            Arc::new(RwLock::new(runner.controller.clone())),
+            logger.clone(),
+            risk_ledger,
+        )
     };
 
-    let handles = funcs::task::runner(cloned_controller.clone(), cloned_logger.clone())
-        .await
-        .unwrap();
+    let (handles, context) = match funcs::task::runner(
+        cloned_controller.clone(),
+        cloned_logger.clone(),
+        cloned_risk_ledger,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(e) => {
+            release_starting(state, runner_id).await;
+            return Err(utils::err_response_handler(
+                "runner is failed to start",
+                &e.to_string(),
+            ));
+        }
+    };
 
     // worker
     let mut workers = Workers::new();
     workers.extend(handles);
+    workers.set_context(context);
     let workers = workers;
 
     let mut controller = {
         let mut w = state.write().await;
-        w.workers = Some(workers);
-        w.controller.clone()
+        let runner = w.runners.entry(runner_id.to_string()).or_default();
+        runner.workers = Some(workers);
+        runner.is_starting = false;
+        runner.controller.clone()
     };
 
     controller.is_running = true;
     Ok(controller)
 }
 
-#[tauri::command]
-pub async fn stop_controller(
-    state: State<'_, Arc<RwLock<AppState>>>,
-) -> Result<client::Controller, Value> {
+// 起動に失敗した場合は予約を解除する
+async fn release_starting(state: &Arc<RwLock<AppState>>, runner_id: &str) {
+    if let Some(runner) = state.write().await.runners.get_mut(runner_id) {
+        runner.is_starting = false;
+    }
+}
+
+// Runnerを停止する
+// 画面からの停止及び取引時間帯による自動停止で共有する
+pub async fn stop_runner(
+    state: &Arc<RwLock<AppState>>,
+    runner_id: &str,
+) -> Result<StopResponse, Value> {
     // workers
     let (mut controller, mut workers) = {
         let mut w = state.write().await;
-        let workers = match w.workers.take() {
+        let runner = match w.runners.get_mut(runner_id) {
+            Some(v) => v,
+            None => return Err(runner_not_found(runner_id)),
+        };
+        let workers = match runner.workers.take() {
             Some(v) => v,
             None => {
                 return Err(utils::err_response_handler(
@@ -88,26 +287,212 @@ pub async fn stop_controller(
                 ));
             }
         };
-        w.workers = None;
-        (w.controller.clone(), workers)
+        (runner.controller.clone(), workers)
     };
 
-    // abort all workers
-    match workers.abort_all().await {
-        Ok(_) => {
-            controller.is_running = false;
-            Ok(controller)
-        }
-        Err(e) => Err(utils::err_response_handler(
-            "abort is failed, workers is not found",
+    // 停止命令を送り、注文のキャンセルを待ってから全タスクを停止する
+    let context = workers.context.clone();
+    if let Err(e) = workers.shutdown(STOP_CANCEL_TIMEOUT * 2).await {
+        return Err(utils::err_response_handler(
+            "shutdown is failed, workers is not found",
             &e.to_string(),
+        ));
+    }
+
+    // 停止後の注文及び建玉
+    let (orders, positions, pnl) = match context {
+        Some(context) => {
+            let mut orders = vec![];
+            for order in context.orders.iter() {
+                orders.push(order.lock().await.clone());
+            }
+            let positions = context.positions.read().await.clone();
+            (orders, positions, Some(summary(&context).await))
+        }
+        None => (vec![], vec![], None),
+    };
+
+    controller.is_running = false;
+    Ok(StopResponse {
+        controller,
+        orders,
+        positions,
+        pnl,
+    })
+}
+
+// 起動中のRunnerの損益及び手数料
+// 完了したRunnerは次の起動または停止まで参照できる
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_pnl(
+    state: State<'_, Arc<RwLock<AppState>>>,
+    runner_id: String,
+) -> Result<pnl::Summary, Value> {
+    let context = {
+        let r = state.read().await;
+        match r.runners.get(&runner_id) {
+            Some(runner) => runner.workers.as_ref().and_then(|w| w.context.clone()),
+            None => return Err(runner_not_found(&runner_id)),
+        }
+    };
+
+    match context {
+        Some(context) => Ok(summary(&context).await),
+        None => Err(utils::err_response_handler(
+            "pnl is not found",
+            "runner is not running, please start runner",
         )),
     }
 }
 
+async fn summary(context: &RunnerContext) -> pnl::Summary {
+    let ltp = context.rx_ticker.borrow().ltp;
+    context.pnl.read().await.summary(ltp)
+}
+
+// 全停止
+// Runnerを即時に停止し、設定中の全銘柄の注文を取引所側で一括キャンセルする
+// runner_idの指定がなければ全てのRunnerを対象とする
+// flattenであれば建玉を成行の決済専用注文で解消する
+#[tauri::command(rename_all = "snake_case")]
+pub async fn kill_switch(
+    state: State<'_, Arc<RwLock<AppState>>>,
+    runner_id: Option<String>,
+    flatten: bool,
+) -> Result<KillResponse, Value> {
+    let mut stopped = vec![];
+    {
+        let mut w = state.write().await;
+        if let Some(runner_id) = runner_id.as_ref() {
+            if !w.runners.contains_key(runner_id) {
+                return Err(runner_not_found(runner_id));
+            }
+        }
+        for (id, runner) in w.runners.iter_mut() {
+            if runner_id.as_ref().is_some_and(|target| target != id) {
+                continue;
+            }
+            stopped.push((
+                id.clone(),
+                runner.controller.clone(),
+                runner.workers.take(),
+                runner.logger.clone(),
+            ));
+        }
+    }
+
+    // 注文タスクによるキャンセルを待たずに中断する
+    // why: 一括キャンセルで取引所側の注文を取り消すため、各注文タスクの終了を待つ必要がない
+    for (_, _, workers, _) in stopped.iter_mut() {
+        if let Some(workers) = workers.as_mut() {
+            if let Some(context) = workers.context.as_ref() {
+                context.cancel_handle.cancel();
+            }
+            if let Err(e) = workers.abort_all().await {
+                debug!("kill switch abort error: {:?}", e);
+            }
+        }
+    }
+
+    // 主側及びヘッジ先の銘柄
+    // 複数のRunnerで同じ銘柄を対象とする場合は一度のみ実行する
+    let mut targets = vec![];
+    for (_, controller, _, _) in stopped.iter() {
+        let mut symbols = vec![(controller.exchange.clone(), controller.order.symbol.clone())];
+        if let Some(hedge) = controller.hedge.as_ref() {
+            symbols.push((hedge.exchange.clone(), hedge.symbol.clone()));
+        }
+        for (exchange_config, symbol) in symbols {
+            if !exchange_config.is_ok() || symbol.is_empty() {
+                continue;
+            }
+            let is_duplicated = targets.iter().any(|(e, s): &(exchange::Config, String)| {
+                e.name.as_str() == exchange_config.name.as_str()
+                    && e.key == exchange_config.key
+                    && *s == symbol
+            });
+            if !is_duplicated {
+                targets.push((exchange_config, symbol));
+            }
+        }
+    }
+
+    let mut results = vec![];
+    for (exchange_config, symbol) in targets.iter() {
+        results.push(kill::kill(exchange_config, symbol, flatten).await);
+    }
+
+    for (_, _, _, logger) in stopped.iter() {
+        if let Some(logger) = logger {
+            let mut w = logger.write().await;
+            for result in results.iter() {
+                w.add(client::Log {
+                    level: "error".to_string(),
+                    message: format!(
+                        "[kill] {} {}, cancelled: {:?}, closed: {:?}, errors: {:?}",
+                        result.exchange,
+                        result.symbol,
+                        result.cancelled,
+                        result.closed,
+                        result.errors
+                    ),
+                    timestamp: chrono::Local::now().to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(KillResponse {
+        runner_ids: stopped.into_iter().map(|(id, _, _, _)| id).collect(),
+        results,
+    })
+}
+
+// 全てのRunnerの状態
 #[tauri::command]
+pub async fn list_runners(
+    state: State<'_, Arc<RwLock<AppState>>>,
+) -> Result<Vec<RunnerItem>, Value> {
+    let runners = {
+        let r = state.read().await;
+        r.runners
+            .iter()
+            .map(|(id, runner)| {
+                (
+                    id.clone(),
+                    runner.status(),
+                    runner.controller.clone(),
+                    runner.workers.as_ref().and_then(|w| w.context.clone()),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut items = vec![];
+    for (runner_id, status, mut controller, context) in runners {
+        controller.is_running = status == RunnerStatus::Running;
+        let pnl = match context {
+            Some(context) => Some(summary(&context).await),
+            None => None,
+        };
+        items.push(RunnerItem {
+            runner_id,
+            status,
+            controller,
+            pnl,
+        });
+    }
+    items.sort_by(|a, b| a.runner_id.cmp(&b.runner_id));
+
+    Ok(items)
+}
+
+// Runnerの設定を登録する
+// 未登録のRunner IDであれば新しいRunnerを追加する
+#[tauri::command(rename_all = "snake_case")]
 pub async fn post_controller(
     state: State<'_, Arc<RwLock<AppState>>>,
+    runner_id: String,
     value: Value,
 ) -> Result<Value, Value> {
     // value bind to Controller
@@ -133,25 +518,32 @@ pub async fn post_controller(
 
     {
         let mut w = state.write().await;
-        w.controller = controller.clone();
+        w.runners.entry(runner_id).or_default().controller = controller.clone();
     }
 
     Ok(json!(controller))
 }
 
 #[tauri::command(rename_all = "snake_case")]
-pub async fn get_controller(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Value, Value> {
+pub async fn get_controller(
+    state: State<'_, Arc<RwLock<AppState>>>,
+    runner_id: String,
+) -> Result<Value, Value> {
     let controller = {
         let r = state.read().await;
-        r.controller.clone()
+        match r.runners.get(&runner_id) {
+            Some(runner) => runner.controller.clone(),
+            None => return Err(runner_not_found(&runner_id)),
+        }
     };
 
     Ok(json!(controller))
 }
 
-#[tauri::command]
+#[tauri::command(rename_all = "snake_case")]
 pub async fn put_controller(
     state: State<'_, Arc<RwLock<AppState>>>,
+    runner_id: String,
     value: Value,
 ) -> Result<Value, Value> {
     // value bind to Controller
@@ -160,21 +552,33 @@ pub async fn put_controller(
 
     {
         let mut w = state.write().await;
-        w.controller = controller.clone();
+        w.runners.entry(runner_id).or_default().controller = controller.clone();
     }
 
     Ok(json!(controller))
 }
 
-#[tauri::command]
+// Runnerを削除する
+// 起動中のRunnerは注文が残るため、停止してから削除する
+#[tauri::command(rename_all = "snake_case")]
 pub async fn delete_controller(
     state: State<'_, Arc<RwLock<AppState>>>,
+    runner_id: String,
 ) -> Result<client::Controller, Value> {
-    let controller = client::Controller::default();
     let mut w = state.write().await;
-    w.controller = controller.clone();
+    match w.runners.get(&runner_id) {
+        Some(runner) if runner.workers.is_some() => {
+            return Err(utils::err_response_handler(
+                "runner is running, please stop runner",
+                &format!("runner id: {}", runner_id),
+            ));
+        }
+        Some(_) => (),
+        None => return Err(runner_not_found(&runner_id)),
+    }
 
-    Ok(controller)
+    let runner = w.runners.remove(&runner_id).unwrap_or_default();
+    Ok(runner.controller)
 }
 
 #[tauri::command(rename_all = "snake_case")]
@@ -207,11 +611,16 @@ pub async fn get_ticker(exchange_name: ExchangeName, symbol: String) -> Result<V
     Ok(json!(ticker))
 }
 
-#[tauri::command]
-pub async fn get_logger(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Value, Value> {
+#[tauri::command(rename_all = "snake_case")]
+pub async fn get_logger(
+    state: State<'_, Arc<RwLock<AppState>>>,
+    runner_id: String,
+) -> Result<Value, Value> {
     let read_logger = {
         let r = state.read().await;
-        r.logger.clone()
+        r.runners
+            .get(&runner_id)
+            .and_then(|runner| runner.logger.clone())
     };
 
     match read_logger {
@@ -219,8 +628,6 @@ pub async fn get_logger(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Value
             let mut w_logger = origin_logger.write().await;
             let send_logger = w_logger.clone();
             w_logger.clear();
-            let mut w = state.write().await;
-            w.logger = Some(origin_logger.clone());
 
             Ok(json!(send_logger.log))
         }
@@ -228,10 +635,15 @@ pub async fn get_logger(state: State<'_, Arc<RwLock<AppState>>>) -> Result<Value
     }
 }
 
-#[tauri::command]
-pub async fn clear_logger(state: State<'_, Arc<RwLock<AppState>>>) -> Result<(), Value> {
+#[tauri::command(rename_all = "snake_case")]
+pub async fn clear_logger(
+    state: State<'_, Arc<RwLock<AppState>>>,
+    runner_id: String,
+) -> Result<(), Value> {
     let mut w = state.write().await;
-    w.logger = None;
+    if let Some(runner) = w.runners.get_mut(&runner_id) {
+        runner.logger = None;
+    }
 
     Ok(())
 }
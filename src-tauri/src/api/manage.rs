@@ -1,18 +1,26 @@
-use log::error;
+use std::time::Duration;
+
+use log::{error, warn};
 use tokio::task::{JoinError, JoinHandle};
 
+use crate::funcs::task::RunnerContext;
+
 pub struct Workers {
     // 返り値を持たない非同期タスクのハンドル
     // .abort() でキャンセル可能
     // .await で終了待ち
     // spawn内で.awaitに対してキャンセル命令を送ることで終了させる
     pub handles: Vec<JoinHandle<()>>,
+
+    // Runnerへの停止命令及び最終状態の参照
+    pub context: Option<RunnerContext>,
 }
 
 impl Workers {
     pub fn new() -> Self {
         Workers {
             handles: Vec::new(),
+            context: None,
         }
     }
 
@@ -20,6 +28,35 @@ impl Workers {
         self.handles.extend(handles);
     }
 
+    pub fn set_context(&mut self, context: RunnerContext) {
+        self.context = Some(context);
+    }
+
+    // 停止命令を送り、注文タスクの終了を待ってから全タスクを中断する
+    // - 注文タスクは注文をキャンセルしてから終了する
+    // - timeout内に終了しない場合も中断する
+    pub async fn shutdown(&mut self, timeout: Duration) -> Result<(), JoinError> {
+        if let Some(context) = self.context.as_ref() {
+            context.cancel_handle.cancel();
+
+            let deadline = tokio::time::Instant::now() + timeout;
+            for finished in context.finished.iter() {
+                if tokio::time::timeout_at(deadline, finished.cancelled())
+                    .await
+                    .is_err()
+                {
+                    warn!("order task is not finished in {:?}, abort", timeout);
+                    break;
+                }
+            }
+        }
+
+        self.abort_all().await?;
+
+        log::logger().flush();
+        Ok(())
+    }
+
     pub async fn abort_all(&mut self) -> Result<(), JoinError> {
         for handle in self.handles.drain(..) {
             handle.abort();
@@ -1,2 +1,3 @@
 pub mod invokers;
 pub mod manage;
+pub mod scheduler;
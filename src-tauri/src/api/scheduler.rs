@@ -0,0 +1,137 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use log::{error, info};
+use tokio::sync::RwLock;
+
+use crate::{
+    api::invokers::{self, AppState},
+    funcs::client::Log,
+};
+
+// 取引時間帯の確認間隔
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+// 取引時間帯の境界でRunnerを起動・停止する
+// 境界を跨いだときのみ操作し、時間帯内の手動停止や時間帯外の手動起動は上書きしない
+// 取引時間帯の設定直後は時間帯内であれば起動し、時間帯外であれば次の境界から操作する
+// why: 時間帯の途中で設定すると、次の境界まで起動しない
+// 各Runnerの取引時間帯は独立して判定する
+pub async fn run(state: Arc<RwLock<AppState>>) {
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+    let mut prev_active: HashMap<String, bool> = HashMap::new();
+
+    loop {
+        interval.tick().await;
+
+        let runners = {
+            let r = state.read().await;
+            r.runners
+                .iter()
+                .filter_map(|(id, runner)| {
+                    runner
+                        .controller
+                        .schedule
+                        .clone()
+                        .map(|schedule| (id.clone(), schedule, runner.workers.is_some()))
+                })
+                .collect::<Vec<_>>()
+        };
+        // 取引時間帯を解除した、または削除したRunnerの記録は破棄する
+        prev_active.retain(|id, _| runners.iter().any(|(runner_id, _, _)| runner_id == id));
+
+        for (runner_id, schedule, is_running) in runners {
+            let is_active = schedule.is_active(Utc::now());
+            let prev = prev_active.insert(runner_id.clone(), is_active);
+            let is_boundary = match prev {
+                Some(prev) => prev != is_active,
+                // 設定直後は時間帯内のみ境界とみなす
+                None => is_active,
+            };
+            if !is_boundary {
+                if prev.is_none() {
+                    add_log(
+                        &state,
+                        &runner_id,
+                        "info",
+                        "[schedule] schedule armed, waiting for next boundary".to_string(),
+                    )
+                    .await;
+                }
+                continue;
+            }
+
+            let event = if prev.is_none() {
+                "schedule armed within window"
+            } else if is_active {
+                "window opened"
+            } else {
+                "window closed"
+            };
+            match (is_active, is_running) {
+                (true, false) => match invokers::start_runner(&state, &runner_id).await {
+                    Ok(_) => {
+                        add_log(
+                            &state,
+                            &runner_id,
+                            "info",
+                            format!("[schedule] {}, runner started", event),
+                        )
+                        .await
+                    }
+                    Err(e) => {
+                        add_log(
+                            &state,
+                            &runner_id,
+                            "error",
+                            format!("[schedule] {}, start error: {}", event, e),
+                        )
+                        .await
+                    }
+                },
+                (false, true) => {
+                    // 停止前のLoggerに記録する
+                    add_log(
+                        &state,
+                        &runner_id,
+                        "info",
+                        format!("[schedule] {}, stopping runner", event),
+                    )
+                    .await;
+                    if let Err(e) = invokers::stop_runner(&state, &runner_id).await {
+                        add_log(
+                            &state,
+                            &runner_id,
+                            "error",
+                            format!("[schedule] {}, stop error: {}", event, e),
+                        )
+                        .await;
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+}
+
+async fn add_log(state: &Arc<RwLock<AppState>>, runner_id: &str, level: &str, message: String) {
+    match level {
+        "error" => error!("[{}] {}", runner_id, message),
+        _ => info!("[{}] {}", runner_id, message),
+    }
+
+    let logger = {
+        let r = state.read().await;
+        r.runners
+            .get(runner_id)
+            .and_then(|runner| runner.logger.clone())
+    };
+    if let Some(logger) = logger {
+        let mut w = logger.write().await;
+        w.add(Log {
+            level: level.to_string(),
+            message,
+            timestamp: chrono::Local::now().to_string(),
+        });
+    }
+}
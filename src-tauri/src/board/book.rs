@@ -14,7 +14,8 @@ use crate::{board::filter::Config, target::exchanges::models::BookSide};
 pub struct Orderboard {
     ask: Arc<RwLock<BTreeMap<OrderedFloat<f64>, Book>>>,
     bid: Arc<RwLock<BTreeMap<OrderedFloat<f64>, Book>>>,
-    update_at: DateTime<Utc>,
+    // 最終更新時刻、板の停滞の検出に使用する
+    update_at: Arc<RwLock<DateTime<Utc>>>,
 }
 
 #[allow(dead_code)]
@@ -23,7 +24,7 @@ impl Orderboard {
         Orderboard {
             ask: Arc::new(RwLock::new(BTreeMap::new())),
             bid: Arc::new(RwLock::new(BTreeMap::new())),
-            update_at: Utc::now(),
+            update_at: Arc::new(RwLock::new(Utc::now())),
         }
     }
 
@@ -85,9 +86,53 @@ impl Orderboard {
         }
     }
 
-    pub fn update_at(&mut self) -> DateTime<Utc> {
-        self.update_at = Utc::now();
-        self.update_at
+    pub fn update_at(&self) -> DateTime<Utc> {
+        let mut update_at = self.update_at.write().unwrap();
+        *update_at = Utc::now();
+        *update_at
+    }
+
+    pub fn last_update_at(&self) -> DateTime<Utc> {
+        *self.update_at.read().unwrap()
+    }
+
+    // 板の整合性を確認し、異常があれば理由を返す
+    // - 最良売り価格が最良買い価格以下（交差・同値）
+    // - 0以下または非数の価格、価格順に並ぶため両端のみ確認する
+    // - 購読した深さを超える段数、削除の差分を取りこぼした場合に残る
+    pub fn integrity_error(&self, depth: usize) -> Option<String> {
+        let ask = self.ask.read().unwrap();
+        let bid = self.bid.read().unwrap();
+
+        for (side, book) in [("ask", &ask), ("bid", &bid)] {
+            let ends = [book.keys().next(), book.keys().next_back()];
+            if let Some(price) = ends
+                .into_iter()
+                .flatten()
+                .find(|p| !(p.0 > 0.0 && p.0.is_finite()))
+            {
+                return Some(format!("invalid {} price: {}", side, price.0));
+            }
+            if depth > 0 && book.len() > depth {
+                return Some(format!(
+                    "{} levels: {} > depth: {}",
+                    side,
+                    book.len(),
+                    depth
+                ));
+            }
+        }
+
+        if let (Some(best_ask), Some(best_bid)) = (ask.keys().next(), bid.keys().next_back()) {
+            if best_ask <= best_bid {
+                return Some(format!(
+                    "crossed book, ask: {} <= bid: {}",
+                    best_ask.0, best_bid.0
+                ));
+            }
+        }
+
+        None
     }
 
     pub fn replace(&self, target_side: BookSide, book: Vec<Book>) {
@@ -185,6 +230,39 @@ impl Orderboard {
         filter_config: &Config,
         prev_own_order_price: Option<f64>,
     ) -> (f64, bool) {
+        let own_prices = prev_own_order_price.into_iter().collect::<Vec<f64>>();
+        match self.target_books(filter_config, 1, &own_prices).first() {
+            Some(price) => (*price, true),
+            None => (0.0, false),
+        }
+    }
+
+    // 壁と同じ価格に並ぶ場合の対象の板を検出する
+    // 自身の注文価格は除外せず、自身の注文数量を差し引いたサイズで判定する
+    // why: 除外すると並んでいる壁自体が候補から外れ、次の壁へ移動してしまう
+    pub fn target_book_joined(
+        &self,
+        filter_config: &Config,
+        prev_own_order: Option<(f64, f64)>,
+    ) -> (f64, bool) {
+        let own_orders = prev_own_order.into_iter().collect::<Vec<(f64, f64)>>();
+        match self
+            .target_books_joined(filter_config, 1, &own_orders)
+            .first()
+        {
+            Some(price) => (*price, true),
+            None => (0.0, false),
+        }
+    }
+
+    // 最良価格側から条件を満たす板を最大count件検出する
+    // 複数の自己注文を出している場合は全ての自己注文価格を除外する
+    pub fn target_books(
+        &self,
+        filter_config: &Config,
+        count: usize,
+        own_prices: &[f64],
+    ) -> Vec<f64> {
         // 複数の filter を連結したクロージャ
         let is_condition = |&(_, book): &(&OrderedFloat<f64>, &Book)| {
             filter_config.is_large(book)
@@ -192,7 +270,52 @@ impl Orderboard {
                 // 自身の注文価格を除外する
                 // 自身の板が検知に引っかかる場合は除外し、次の候補を探す
                 // これにより、自板の後ろに板が引いたときには引いた価格に注文する
-                && !filter_config.is_excluded(book, prev_own_order_price)
+                && !own_prices
+                    .iter()
+                    .any(|price| filter_config.is_excluded(book, Some(*price)))
+        };
+
+        self.find(filter_config, count, is_condition)
+    }
+
+    // 壁と同じ価格に並ぶ場合に条件を満たす板を最大count件検出する
+    pub fn target_books_joined(
+        &self,
+        filter_config: &Config,
+        count: usize,
+        own_orders: &[(f64, f64)],
+    ) -> Vec<f64> {
+        let is_condition = |&(_, book): &(&OrderedFloat<f64>, &Book)| {
+            // 同じ価格の自己注文の数量を合算して差し引く
+            let own_qty = own_orders
+                .iter()
+                .filter(|(price, _)| *price == book.price)
+                .map(|(_, qty)| qty)
+                .sum::<f64>();
+            filter_config.is_large_without_own(book, Some((book.price, own_qty)))
+                && filter_config.is_range(book)
+        };
+
+        self.find(filter_config, count, is_condition)
+    }
+
+    fn find<F>(&self, filter_config: &Config, count: usize, is_condition: F) -> Vec<f64>
+    where
+        F: Fn(&(&OrderedFloat<f64>, &Book)) -> bool,
+    {
+        let mut prices = Vec::with_capacity(count);
+        if count == 0 {
+            return prices;
+        }
+
+        // キーと値が整合しているかチェック
+        // 整合しない板を検出した場合はそれ以降を探索しない
+        let mut push = |price: &OrderedFloat<f64>, book: &Book| -> bool {
+            if !book.is_same(price.0) || book.is_zero() {
+                return false;
+            }
+            prices.push(price.0);
+            prices.len() < count
         };
 
         match filter_config.side {
@@ -205,33 +328,24 @@ impl Orderboard {
                 // 検索の該当配列を出力していたが、発見後即時返り値を生成する使用に変更
                 // 可読性が向上し、速度も向上する
                 for (price, book) in abook.iter() {
-                    if is_condition(&(price, book)) {
-                        // キーと値が整合しているかチェック
-                        if book.is_same(price.0) && !book.is_zero() {
-                            return (price.0, true);
-                        } else {
-                            return (0.0, false);
-                        }
+                    if is_condition(&(price, book)) && !push(price, book) {
+                        break;
                     }
                 }
-                (0.0, false)
             }
             BookSide::Bid => {
                 let bbook = self.bid.read().unwrap();
 
                 // Bid は昇順になっているため、逆方向から探す
                 for (price, book) in bbook.iter().rev() {
-                    if is_condition(&(price, book)) {
-                        if book.is_same(price.0) && !book.is_zero() {
-                            return (price.0, true);
-                        } else {
-                            return (0.0, false);
-                        }
+                    if is_condition(&(price, book)) && !push(price, book) {
+                        break;
                     }
                 }
-                (0.0, false)
             }
         }
+
+        prices
     }
 }
 
@@ -401,6 +515,55 @@ mod tests {
         assert_eq!(best_ask, expected_best_ask);
     }
 
+    #[test]
+    fn test_target_book_ask_joined() {
+        let price_max = 10000;
+        let price_min = 1;
+
+        let wall_price = rand::rng().random_range(7..99);
+        let board = setup_board(price_min, price_max, wall_price, BookSide::Ask);
+        let config = create_config(BookSide::Ask, price_max + 1, price_min - 1);
+
+        // 壁に並んでいても、自身の数量を除いて指定サイズを超えていれば同じ壁を対象とする
+        let (price, is_found) = board.target_book_joined(&config, Some((wall_price as f64, 0.1)));
+        assert!(is_found);
+        assert_eq!(price, wall_price as f64);
+
+        // 自身の数量を除くと指定サイズ以下であれば、次の壁を対象とする
+        let (price, is_found) = board.target_book_joined(&config, Some((wall_price as f64, 0.5)));
+        assert!(is_found);
+        assert_eq!(price, (wall_price * 2) as f64);
+    }
+
+    #[test]
+    fn test_target_books_bid() {
+        let price_max = 10000;
+        let price_min = 1;
+
+        let wall_price = rand::rng().random_range(7..99);
+        let board = setup_board(price_min, price_max, wall_price, BookSide::Bid);
+        let config = create_config(BookSide::Bid, price_max + 1, price_min - 1);
+
+        // 最良価格側から上位3件の壁を検出する
+        let top = price_max / wall_price * wall_price;
+        let prices = board.target_books(&config, 3, &[]);
+        assert_eq!(
+            prices,
+            vec![
+                top as f64,
+                (top - wall_price) as f64,
+                (top - wall_price * 2) as f64
+            ]
+        );
+
+        // 自己注文の価格は全て除外する
+        let prices = board.target_books(&config, 2, &[top as f64, (top - wall_price) as f64]);
+        assert_eq!(
+            prices,
+            vec![(top - wall_price * 2) as f64, (top - wall_price * 3) as f64]
+        );
+    }
+
     #[test]
     fn test_target_book_bid_with_prev_exclusion() {
         let price_max = 10000;
@@ -434,6 +597,22 @@ mod tests {
         assert_eq!(best_bid, expected_best_bid);
     }
 
+    #[test]
+    fn test_integrity_error() {
+        let board = Orderboard::new();
+        board.replace_ask(vec![Book::new(1.0, 101.0), Book::new(1.0, 102.0)]);
+        board.replace_bid(vec![Book::new(1.0, 100.0), Book::new(1.0, 99.0)]);
+        assert_eq!(board.integrity_error(2), None);
+        assert!(board.integrity_error(1).unwrap().contains("levels"));
+
+        // 同値の場合も異常とする
+        board.update_delta(BookSide::Bid, vec![Book::new(1.0, 101.0)]);
+        assert!(board.integrity_error(0).unwrap().contains("crossed"));
+
+        board.replace_bid(vec![Book::new(1.0, f64::NAN)]);
+        assert!(board.integrity_error(0).unwrap().contains("invalid bid"));
+    }
+
     #[test]
     fn n_count_try() {
         let start = Instant::now();
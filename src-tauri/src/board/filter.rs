@@ -31,6 +31,15 @@ impl Config {
         self.size < book.size
     }
 
+    // 自身の注文数量を差し引いても指定サイズより大きければtrue
+    // 壁に並ぶ場合、自身の注文価格を除外すると壁自体が検知されなくなるため、数量で判定する
+    pub fn is_large_without_own(&self, book: &Book, own: Option<(f64, f64)>) -> bool {
+        match own {
+            Some((price, qty)) if price == book.price => self.size < book.size - qty,
+            _ => self.is_large(book),
+        }
+    }
+
     // 自身の注文価格と同じ価格の注文であればtrue
     // 自身の注文価格がなければfalse
     pub fn is_excluded(&self, book: &Book, own_price: Option<f64>) -> bool {
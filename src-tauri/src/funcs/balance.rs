@@ -0,0 +1,165 @@
+use crate::board;
+use crate::funcs::client::Controller;
+use crate::target::exchanges::models::{Balance, OrderSide, ToExchange};
+use crate::target::order;
+
+// 起動前に残高を取得し、各側の注文に足りるかを確認する
+// why: 残高不足のまま起動すると、全ての注文が残高不足で失敗し続ける
+pub async fn verify(controller: &Controller) -> Result<(), String> {
+    let client = ToExchange::create_client(&controller.exchange, controller.order.symbol.clone());
+    let balance = match client.balance().await {
+        Ok(Some(v)) => v,
+        // 非対応の取引所は確認しない
+        Ok(None) => return Ok(()),
+        Err(e) => return Err(format!("balance error: {}", e)),
+    };
+
+    check(&controller.legs(), &balance)
+}
+
+// 各側の指定数量を対象範囲の上限価格で注文した場合に必要な残高と比較する
+// 決済専用注文は残高を使用しないため除外する
+pub fn check(
+    legs: &[(board::filter::Config, order::Config)],
+    balance: &Balance,
+) -> Result<(), String> {
+    let legs = legs
+        .iter()
+        .filter(|(_, o)| !o.reduce_only)
+        .collect::<Vec<_>>();
+    let detail = |side: OrderSide| {
+        legs.iter()
+            .filter(|(_, o)| o.side == side)
+            .map(|(b, o)| format!("{:?} {} @ {}", o.side, o.size, b.hight))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let notional = |side: OrderSide| {
+        legs.iter()
+            .filter(|(_, o)| o.side == side)
+            .map(|(b, o)| o.size * b.hight)
+            .sum::<f64>()
+    };
+
+    if balance.is_spot {
+        // 買いは決済通貨、売りは基軸通貨を使用する
+        let required = notional(OrderSide::Buy);
+        if required > balance.quote_available {
+            return Err(format!(
+                "insufficient {} balance: required {} ({}) > available {}",
+                balance.quote_coin,
+                required,
+                detail(OrderSide::Buy),
+                balance.quote_available
+            ));
+        }
+
+        let required = legs
+            .iter()
+            .filter(|(_, o)| o.side == OrderSide::Sell)
+            .map(|(_, o)| o.size)
+            .sum::<f64>();
+        if required > balance.base_available {
+            return Err(format!(
+                "insufficient {} balance: required {} ({}) > available {}",
+                balance.base_coin,
+                required,
+                detail(OrderSide::Sell),
+                balance.base_available
+            ));
+        }
+
+        return Ok(());
+    }
+
+    // 両側に注文する場合、証拠金は大きい方の側に対して拘束される
+    let leverage = balance.leverage.max(1.0);
+    let (buy, sell) = (notional(OrderSide::Buy), notional(OrderSide::Sell));
+    let (side, required) = if buy >= sell {
+        (OrderSide::Buy, buy / leverage)
+    } else {
+        (OrderSide::Sell, sell / leverage)
+    };
+    if required > balance.available_margin {
+        return Err(format!(
+            "insufficient margin: required {} ({} / leverage {}) > available {}",
+            required,
+            detail(side),
+            leverage,
+            balance.available_margin
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::target::exchanges::models::BookSide;
+
+    fn leg(side: OrderSide, size: f64, hight: f64) -> (board::filter::Config, order::Config) {
+        (
+            board::filter::Config {
+                side: BookSide::Bid,
+                hight,
+                low: 0.0,
+                size: 1.0,
+            },
+            order::Config {
+                side,
+                size,
+                ..Default::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_check_margin_with_leverage() {
+        let balance = Balance {
+            available_margin: 1_000.0,
+            leverage: 5.0,
+            ..Default::default()
+        };
+
+        // 0.1 * 40000 / 5 = 800
+        assert!(check(&[leg(OrderSide::Buy, 0.1, 40_000.0)], &balance).is_ok());
+        // 0.2 * 40000 / 5 = 1600
+        let e = check(&[leg(OrderSide::Buy, 0.2, 40_000.0)], &balance).unwrap_err();
+        assert!(e.starts_with("insufficient margin: required 1600"));
+        // 両側の場合は合計ではなく大きい方の側で判定する
+        let legs = [
+            leg(OrderSide::Buy, 0.1, 40_000.0),
+            leg(OrderSide::Sell, 0.1, 45_000.0),
+        ];
+        assert!(check(&legs, &balance).is_ok());
+        let legs = [
+            leg(OrderSide::Buy, 0.1, 40_000.0),
+            leg(OrderSide::Sell, 0.1, 55_000.0),
+        ];
+        let e = check(&legs, &balance).unwrap_err();
+        assert!(e.contains("Sell 0.1 @ 55000"));
+    }
+
+    #[test]
+    fn test_check_spot_coins() {
+        let balance = Balance {
+            is_spot: true,
+            leverage: 1.0,
+            base_coin: "BTC".to_string(),
+            base_available: 0.05,
+            quote_coin: "USDT".to_string(),
+            quote_available: 5_000.0,
+            ..Default::default()
+        };
+
+        assert!(check(&[leg(OrderSide::Buy, 0.1, 40_000.0)], &balance).is_ok());
+        let e = check(&[leg(OrderSide::Sell, 0.1, 40_000.0)], &balance).unwrap_err();
+        assert!(e.starts_with("insufficient BTC balance"));
+
+        // 決済専用注文は除外する
+        let mut reduce = leg(OrderSide::Sell, 0.1, 40_000.0);
+        reduce.1.reduce_only = true;
+        assert!(check(&[reduce], &balance).is_ok());
+    }
+}
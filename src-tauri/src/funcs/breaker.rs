@@ -0,0 +1,188 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+// 急変時に注文を取り下げて一時停止する設定
+// 各上限は0で無効
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Config {
+    // 計測期間
+    pub window_sec: u64,
+    // 期間内の実現ボラティリティの上限（bps）
+    #[serde(default)]
+    pub max_volatility_bps: f64,
+    // 期間内の価格変化率の上限（bps）
+    #[serde(default)]
+    pub max_change_bps: f64,
+    // 停止後に注文を再開するまでの秒数
+    pub cooldown_sec: u64,
+}
+
+impl Config {
+    pub fn is_ok(&self) -> bool {
+        self.window_sec > 0 && (self.max_volatility_bps > 0.0 || self.max_change_bps > 0.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    // 上限を超えたため、再開時刻まで停止する
+    Trip { reason: String, until: Instant },
+    // 再開時刻を過ぎ、上限内に戻った
+    Resume,
+}
+
+// 期間内の価格から急変を検出する
+// 時刻は呼び出し側から受け取り、単体で検証できるようにする
+pub struct Breaker {
+    config: Config,
+    samples: VecDeque<(Instant, f64)>,
+    until: Option<Instant>,
+}
+
+impl Breaker {
+    pub fn new(config: Config) -> Self {
+        Breaker {
+            config,
+            samples: VecDeque::new(),
+            until: None,
+        }
+    }
+
+    // 価格を追加し、停止・再開の境界であればイベントを返す
+    pub fn update(&mut self, now: Instant, price: f64) -> Option<Event> {
+        if price <= 0.0 {
+            return None;
+        }
+
+        self.samples.push_back((now, price));
+        let window = Duration::from_secs(self.config.window_sec);
+        while let Some((at, _)) = self.samples.front() {
+            if now.duration_since(*at) <= window {
+                break;
+            }
+            self.samples.pop_front();
+        }
+
+        // 停止中は再開時刻まで判定しない
+        if self.until.is_some_and(|until| now < until) {
+            return None;
+        }
+
+        if let Some(reason) = self.check() {
+            let until = now + Duration::from_secs(self.config.cooldown_sec);
+            self.until = Some(until);
+            return Some(Event::Trip { reason, until });
+        }
+
+        self.until.take().map(|_| Event::Resume)
+    }
+
+    fn check(&self) -> Option<String> {
+        let volatility = self.volatility_bps();
+        if self.config.max_volatility_bps > 0.0 && volatility > self.config.max_volatility_bps {
+            return Some(format!(
+                "volatility: {:.2}bps > {}bps in {}s",
+                volatility, self.config.max_volatility_bps, self.config.window_sec
+            ));
+        }
+
+        let change = self.change_bps();
+        if self.config.max_change_bps > 0.0 && change.abs() > self.config.max_change_bps {
+            return Some(format!(
+                "price change: {:.2}bps > {}bps in {}s",
+                change, self.config.max_change_bps, self.config.window_sec
+            ));
+        }
+
+        None
+    }
+
+    // 連続する価格の対数変化の二乗和の平方根
+    fn volatility_bps(&self) -> f64 {
+        let sum = self
+            .samples
+            .iter()
+            .zip(self.samples.iter().skip(1))
+            .map(|((_, prev), (_, next))| (next / prev).ln().powi(2))
+            .sum::<f64>();
+
+        sum.sqrt() * 10_000.0
+    }
+
+    // 期間の最初の価格からの変化率
+    fn change_bps(&self) -> f64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some((_, first)), Some((_, last))) => (last - first) / first * 10_000.0,
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> Config {
+        Config {
+            window_sec: 10,
+            max_volatility_bps: 0.0,
+            max_change_bps: 50.0,
+            cooldown_sec: 30,
+        }
+    }
+
+    #[test]
+    fn test_breaker_trips_and_resumes() {
+        let mut breaker = Breaker::new(config());
+        let start = Instant::now();
+        let at = |sec: u64| start + Duration::from_secs(sec);
+
+        assert_eq!(breaker.update(at(0), 100.0), None);
+        assert_eq!(breaker.update(at(1), 100.3), None);
+        // 0.6%の上昇
+        let until = match breaker.update(at(2), 100.6) {
+            Some(Event::Trip { until, .. }) => until,
+            v => panic!("unexpected: {:?}", v),
+        };
+        assert_eq!(until, at(32));
+
+        // 停止中は判定しない
+        assert_eq!(breaker.update(at(20), 100.6), None);
+        // 期間外の価格は除外され、上限内に戻っている
+        assert_eq!(breaker.update(at(32), 100.6), Some(Event::Resume));
+        assert_eq!(breaker.update(at(33), 100.6), None);
+    }
+
+    #[test]
+    fn test_breaker_volatility() {
+        let mut breaker = Breaker::new(Config {
+            max_volatility_bps: 30.0,
+            max_change_bps: 0.0,
+            ..config()
+        });
+        let start = Instant::now();
+
+        // 往復するため変化率は小さいが、ボラティリティは大きい
+        let prices = [100.0, 100.2, 100.0, 100.2, 100.0];
+        let events = prices
+            .iter()
+            .enumerate()
+            .filter_map(|(i, price)| breaker.update(start + Duration::from_secs(i as u64), *price))
+            .collect::<Vec<_>>();
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], Event::Trip { .. }));
+    }
+
+    #[test]
+    fn test_breaker_ignores_empty_price() {
+        let mut breaker = Breaker::new(config());
+        let now = Instant::now();
+
+        assert_eq!(breaker.update(now, 100.0), None);
+        assert_eq!(breaker.update(now, 0.0), None);
+        assert_eq!(breaker.samples.len(), 1);
+    }
+}
@@ -3,7 +3,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     board,
-    target::{exchange, order},
+    funcs::{breaker, hedge, pnl, risk, schedule, stale},
+    target::{exchange, exchanges::models::OrderSide, order},
 };
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
@@ -15,6 +16,36 @@ pub struct Controller {
     pub board: board::filter::Config,
     // 対象取引
     pub order: order::Config,
+    // 両側で追従する場合の反対側
+    // 板の購読及び建玉の上限は主側と共有する
+    #[serde(default)]
+    pub opposite: Option<Leg>,
+    // 取引時間帯、指定があれば時間帯の境界で自動的に起動・停止する
+    #[serde(default)]
+    pub schedule: Option<schedule::Schedule>,
+    // 約定時に別の取引所または銘柄で反対売買する
+    #[serde(default)]
+    pub hedge: Option<hedge::Config>,
+    // 急変時に注文を取り下げて一時停止する
+    #[serde(default)]
+    pub breaker: Option<breaker::Config>,
+    // 板またはTickerの更新が途絶えた場合に注文を取り下げて一時停止する
+    #[serde(default)]
+    pub stale: Option<stale::Config>,
+    // 発注前の上限、未指定の項目は制限しない
+    #[serde(default)]
+    pub risk: risk::Config,
+    // 損益の集計に使用する手数料率
+    #[serde(default)]
+    pub fee: pnl::FeeConfig,
+}
+
+// 反対側の追従設定
+// 注文設定は主側を引き継ぎ、売買方向を反転する
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Leg {
+    pub board: board::filter::Config,
+    pub size: f64,
 }
 
 impl Controller {
@@ -27,10 +58,50 @@ impl Controller {
             Err("board setting is empty")
         } else if !self.order.is_ok() {
             Err("order setting is empty")
+        } else if self
+            .opposite
+            .as_ref()
+            .is_some_and(|leg| !leg.board.is_ok() || leg.size <= 0.0)
+        {
+            Err("opposite setting is empty")
+        } else if self.schedule.as_ref().is_some_and(|s| !s.is_ok()) {
+            Err("schedule setting is invalid")
+        } else if self.hedge.as_ref().is_some_and(|h| !h.is_ok()) {
+            Err("hedge setting is empty")
+        } else if self.breaker.as_ref().is_some_and(|b| !b.is_ok()) {
+            Err("breaker setting is invalid")
+        } else if self.stale.as_ref().is_some_and(|s| !s.is_ok()) {
+            Err("stale setting is invalid")
         } else {
             Ok(())
         }
     }
+
+    // 追従する各側の板設定及び注文設定
+    // 先頭が主側、反対側があれば2番目
+    pub fn legs(&self) -> Vec<(board::filter::Config, order::Config)> {
+        let mut legs = vec![(self.board.clone(), self.order.clone())];
+        if let Some(leg) = self.opposite.as_ref() {
+            let mut order = self.order.clone();
+            order.side = match self.order.side {
+                OrderSide::Buy => OrderSide::Sell,
+                OrderSide::Sell => OrderSide::Buy,
+            };
+            order.size = leg.size;
+            legs.push((leg.board.clone(), order));
+        }
+
+        legs
+    }
+
+    // 同じ口座・銘柄に発注する設定か
+    // why: 自己注文IDは取引所・銘柄から生成するため、同時に起動すると互いの注文を区別できない
+    pub fn is_same_target(&self, other: &Controller) -> bool {
+        self.exchange.name.as_str() == other.exchange.name.as_str()
+            && self.exchange.key == other.exchange.key
+            && self.exchange.category == other.exchange.category
+            && self.order.symbol == other.order.symbol
+    }
 }
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
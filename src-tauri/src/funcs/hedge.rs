@@ -0,0 +1,261 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::funcs::position;
+use crate::target::exchange::{self, ExchangeName};
+use crate::target::exchanges::bybit;
+use crate::target::exchanges::models::{
+    ErrorKind, ExchangeError, Execution, OrderParams, OrderSide, OrderType, Ticker, TimeInForce,
+    ToExchange,
+};
+
+// 約定時に別の取引所または銘柄で反対売買する設定
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Config {
+    // ヘッジ先の取引所
+    pub exchange: exchange::Config,
+    pub symbol: String,
+    // 約定数量に対するヘッジ数量の比率
+    pub ratio: f64,
+    // 指値の場合はヘッジ先の最良価格で即時執行のみ行う
+    #[serde(default)]
+    pub order_type: OrderType,
+    // ヘッジ先の数量刻み、0の場合は丸めない
+    #[serde(default)]
+    pub qty_step: f64,
+}
+
+impl Config {
+    pub fn is_ok(&self) -> bool {
+        self.exchange.is_ok() && !self.symbol.is_empty() && self.ratio > 0.0
+    }
+
+    // 累計約定数量に対して未ヘッジの数量
+    // 刻み未満の端数は次の約定に繰り越す
+    pub fn hedge_qty(&self, filled_qty: f64, hedged_qty: f64) -> f64 {
+        let qty = filled_qty * self.ratio - hedged_qty;
+        if self.qty_step > 0.0 {
+            ((qty / self.qty_step) + 1e-9).floor() * self.qty_step
+        } else {
+            qty
+        }
+    }
+}
+
+// ヘッジ注文の約定結果の確認間隔及び回数
+const EXECUTION_INTERVAL: Duration = Duration::from_millis(200);
+const EXECUTION_MAX_RETRY: usize = 10;
+
+// ヘッジ注文の結果
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub side: OrderSide,
+    // 約定数量、即時執行のため発注数量に満たない場合がある
+    pub qty: f64,
+    // 平均約定価格
+    pub price: f64,
+    // 発注時点のヘッジ先の最良価格
+    pub expected_price: f64,
+    // 約定価格に対する平均約定価格の乖離
+    pub slippage_bps: f64,
+}
+
+// ヘッジ先の注文クライアント
+// 主側とは別の取引所クライアントを保持する
+pub struct Hedger {
+    config: Config,
+    client: ToExchange,
+    // ヘッジ注文の連番
+    counter: AtomicU32,
+}
+
+impl Hedger {
+    pub fn new(config: Config) -> Self {
+        let client = ToExchange::create_client(&config.exchange, config.symbol.clone());
+        Hedger {
+            config,
+            client,
+            counter: AtomicU32::new(0),
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    // 約定結果を自己注文IDで照会するため、ヘッジ注文ごとに別のIDとする
+    pub fn order_id(&self) -> String {
+        order_id(
+            chrono::Utc::now().timestamp_millis(),
+            self.counter.fetch_add(1, Ordering::Relaxed),
+        )
+    }
+
+    // 約定した注文と反対方向の注文を作成する
+    // 相手側の最良価格を約定見込み価格とする
+    pub async fn params(
+        &self,
+        order_id: String,
+        entry_side: &OrderSide,
+        qty: f64,
+    ) -> Result<OrderParams, ExchangeError> {
+        let side = match entry_side {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        };
+
+        let ticker = self.ticker().await?;
+        let expected_price = match side {
+            OrderSide::Buy => ticker.best_ask,
+            OrderSide::Sell => ticker.best_bid,
+        };
+
+        Ok(OrderParams {
+            order_id: Some(order_id),
+            side,
+            price: expected_price,
+            qty,
+            order_type: self.config.order_type.clone(),
+            // why: ヘッジ注文が板に残ると、約定するまで露出が解消しない
+            time_in_force: TimeInForce::Ioc,
+            reduce_only: false,
+        })
+    }
+
+    // ヘッジ先の建玉、売買方向を符号とする
+    pub async fn net_position(&self) -> Result<f64, ExchangeError> {
+        Ok(position::net_position(&self.client.positions().await?))
+    }
+
+    // ヘッジ注文を発注し、約定結果を読み戻す
+    // テストの場合は発注せず、見込み価格で全量約定したとみなす
+    pub async fn hedge(
+        &self,
+        params: &OrderParams,
+        entry_side: &OrderSide,
+        entry_price: f64,
+        is_test: bool,
+    ) -> Result<Fill, ExchangeError> {
+        let expected_price = params.price;
+        if is_test {
+            return Ok(Fill {
+                side: params.side.clone(),
+                qty: params.qty,
+                price: expected_price,
+                expected_price,
+                slippage_bps: slippage_bps(entry_side, entry_price, expected_price),
+            });
+        }
+
+        self.client.place_order(params).await?;
+        // why: IOCは一部のみ約定して残りが取り消される場合があり、発注数量をヘッジ済みとすると露出が残る
+        let (qty, price) = self
+            .wait_execution(params.order_id.clone().unwrap_or_default())
+            .await?;
+
+        Ok(Fill {
+            side: params.side.clone(),
+            qty,
+            price,
+            expected_price,
+            slippage_bps: slippage_bps(entry_side, entry_price, price),
+        })
+    }
+
+    // 読み戻せなかったヘッジ注文の約定状況を照会する
+    // 注文が存在しない場合はNone
+    pub async fn execution(&self, order_id: String) -> Result<Option<Execution>, ExchangeError> {
+        self.client.execution(order_id).await
+    }
+
+    // 注文の処理が終了するまで待ち、約定数量及び平均約定価格を取得する
+    async fn wait_execution(&self, order_id: String) -> Result<(f64, f64), ExchangeError> {
+        for _ in 0..EXECUTION_MAX_RETRY {
+            match self.client.execution(order_id.clone()).await? {
+                Some(v) if v.is_closed => return Ok((v.cum_exec_qty, v.average_price)),
+                _ => tokio::time::sleep(EXECUTION_INTERVAL).await,
+            }
+        }
+
+        Err(ExchangeError::new(
+            ErrorKind::Retryable,
+            0,
+            format!("execution not closed: {}", order_id),
+        ))
+    }
+
+    async fn ticker(&self) -> Result<Ticker, ExchangeError> {
+        match self.config.exchange.name {
+            ExchangeName::Bybit => {
+                let category = self
+                    .config
+                    .exchange
+                    .category
+                    .clone()
+                    .unwrap_or(bybit::DEFAULT_CATEGORY.to_string());
+                bybit::ticker(category, self.config.symbol.clone())
+                    .await
+                    .map_err(|e| ExchangeError::new(ErrorKind::Retryable, 0, e))
+            }
+            ExchangeName::Bitbank => Ok(Ticker::default()),
+            ExchangeName::Bitflyer => Ok(Ticker::default()),
+        }
+    }
+}
+
+// why: 主側の自己注文IDを含めると上限（Bybitは36文字）を超える場合がある
+fn order_id(timestamp: i64, n: u32) -> String {
+    format!("hedge_{}_{}", timestamp, n)
+}
+
+// 約定価格に対するヘッジ価格の乖離
+// 正の値が不利な方向
+pub fn slippage_bps(entry_side: &OrderSide, entry_price: f64, hedge_price: f64) -> f64 {
+    if entry_price <= 0.0 || hedge_price <= 0.0 {
+        return 0.0;
+    }
+
+    let diff = match entry_side {
+        OrderSide::Buy => entry_price - hedge_price,
+        OrderSide::Sell => hedge_price - entry_price,
+    };
+    diff / entry_price * 10_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hedge_qty() {
+        let config = Config {
+            ratio: 0.5,
+            qty_step: 0.01,
+            ..Default::default()
+        };
+
+        assert!((config.hedge_qty(0.1, 0.0) - 0.05).abs() < 1e-9);
+        // 刻み未満は繰り越す
+        assert_eq!(config.hedge_qty(0.11, 0.05), 0.0);
+        assert!((config.hedge_qty(0.12, 0.05) - 0.01).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_order_id() {
+        // 2100年時点の時刻及び連番の上限でも自己注文IDの上限に収まる
+        let id = order_id(4_102_444_800_000, u32::MAX);
+        assert!(id.len() <= 36, "{}", id);
+        assert_ne!(order_id(1, 0), order_id(1, 1));
+    }
+
+    #[test]
+    fn test_slippage_bps() {
+        // 100で買い、99.9で売りヘッジ
+        assert!((slippage_bps(&OrderSide::Buy, 100.0, 99.9) - 10.0).abs() < 1e-9);
+        // 100で売り、99.9で買いヘッジは有利
+        assert!((slippage_bps(&OrderSide::Sell, 100.0, 99.9) + 10.0).abs() < 1e-9);
+        assert_eq!(slippage_bps(&OrderSide::Buy, 0.0, 99.9), 0.0);
+    }
+}
@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::target::exchange;
+use crate::target::exchanges::models::{
+    OrderParams, OrderSide, OrderType, TimeInForce, ToExchange,
+};
+
+// 銘柄ごとの全停止の結果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KillResult {
+    pub exchange: String,
+    pub symbol: String,
+    // キャンセルした注文ID
+    pub cancelled: Vec<String>,
+    // 決済した建玉
+    pub closed: Vec<Closed>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Closed {
+    pub side: OrderSide,
+    pub qty: f64,
+}
+
+// 対象銘柄の全注文をキャンセルし、flattenであれば建玉を成行の決済専用注文で解消する
+// 失敗はresultに記録し、呼び出し側で他の銘柄の処理を継続できるようにする
+pub async fn kill(exchange_config: &exchange::Config, symbol: &str, flatten: bool) -> KillResult {
+    let client = ToExchange::create_client(exchange_config, symbol.to_string());
+    let mut result = KillResult {
+        exchange: exchange_config.name.as_str().to_string(),
+        symbol: symbol.to_string(),
+        ..Default::default()
+    };
+
+    match client.cancel_all().await {
+        Ok(order_ids) => result.cancelled = order_ids,
+        Err(e) => result.errors.push(format!("cancel all error: {}", e)),
+    }
+
+    if !flatten {
+        return result;
+    }
+
+    let positions = match client.positions().await {
+        Ok(v) => v,
+        Err(e) => {
+            result.errors.push(format!("position error: {}", e));
+            return result;
+        }
+    };
+
+    // why: 取引所・銘柄名を含めると自己注文IDの上限（Bybitは36文字）を超える場合がある
+    let timestamp = chrono::Utc::now().timestamp_millis();
+    for (i, position) in positions.iter().enumerate() {
+        let side = match position.side.as_str() {
+            "Sell" | "sell" => OrderSide::Buy,
+            _ => OrderSide::Sell,
+        };
+        let params = OrderParams {
+            order_id: Some(format!("kill_{}_{}", timestamp, i)),
+            side: side.clone(),
+            price: 0.0,
+            qty: position.qty,
+            order_type: OrderType::Market,
+            time_in_force: TimeInForce::Ioc,
+            reduce_only: true,
+        };
+
+        match client.place_order(&params).await {
+            Ok(_) => result.closed.push(Closed {
+                side,
+                qty: position.qty,
+            }),
+            Err(e) => result
+                .errors
+                .push(format!("close {:?} {} error: {}", side, position.qty, e)),
+        }
+    }
+
+    result
+}
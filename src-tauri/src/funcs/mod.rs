@@ -1,4 +1,14 @@
+pub mod balance;
+pub mod breaker;
 pub mod client;
+pub mod hedge;
+pub mod kill;
+pub mod pnl;
 pub mod position;
+pub mod reconcile;
+pub mod risk;
+pub mod schedule;
+pub mod stale;
+pub mod strategy;
 pub mod task;
 pub mod utils;
@@ -0,0 +1,253 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::target::exchanges::models::OrderSide;
+
+// 手数料率（bps）
+// メイカーのリベートは負の値で指定する
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct FeeConfig {
+    #[serde(default)]
+    pub maker_bps: f64,
+    #[serde(default)]
+    pub taker_bps: f64,
+}
+
+// 約定
+#[derive(Debug, Clone)]
+pub struct Fill {
+    pub side: OrderSide,
+    pub qty: f64,
+    pub price: f64,
+    // 板に並んで約定した場合はtrue
+    pub is_maker: bool,
+}
+
+// Runnerごとの損益の集計結果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Summary {
+    pub symbol: String,
+    // 売買方向を符号とした建玉及び平均取得価格
+    pub net_position: f64,
+    pub average_price: f64,
+    pub realized_pnl: f64,
+    // 最終約定価格で評価した建玉の損益
+    pub unrealized_pnl: f64,
+    pub ltp: f64,
+    pub fees: f64,
+    // 手数料を差し引いた損益
+    pub net_pnl: f64,
+    // 約定代金
+    pub maker_volume: f64,
+    pub taker_volume: f64,
+    pub fills: usize,
+}
+
+// 1回の起動の間の約定から損益及び手数料を集計する
+// 建玉は平均取得価格で管理し、反対方向の約定で確定損益とする
+#[derive(Debug, Default)]
+pub struct Tracker {
+    symbol: String,
+    fee: FeeConfig,
+    position: f64,
+    average_price: f64,
+    realized_pnl: f64,
+    fees: f64,
+    maker_volume: f64,
+    taker_volume: f64,
+    fills: usize,
+    // 注文IDごとの集計済みの累計約定数量及び約定代金
+    cumulative: HashMap<String, (f64, f64)>,
+    // 当日の損益に計上済みの手数料控除後の確定損益
+    booked_pnl: f64,
+}
+
+impl Tracker {
+    pub fn new(symbol: String, fee: FeeConfig) -> Self {
+        Tracker {
+            symbol,
+            fee,
+            ..Default::default()
+        }
+    }
+
+    pub fn record(&mut self, fill: &Fill) {
+        if fill.qty <= 0.0 || fill.price <= 0.0 {
+            return;
+        }
+
+        let notional = fill.qty * fill.price;
+        let fee_bps = if fill.is_maker {
+            self.maker_volume += notional;
+            self.fee.maker_bps
+        } else {
+            self.taker_volume += notional;
+            self.fee.taker_bps
+        };
+        self.fees += notional * fee_bps / 10_000.0;
+        self.fills += 1;
+
+        let signed_qty = match fill.side {
+            OrderSide::Buy => fill.qty,
+            OrderSide::Sell => -fill.qty,
+        };
+        // 同じ方向であれば平均取得価格を更新する
+        if self.position == 0.0 || self.position.signum() == signed_qty.signum() {
+            let qty = self.position.abs() + fill.qty;
+            self.average_price =
+                (self.average_price * self.position.abs() + fill.price * fill.qty) / qty;
+            self.position += signed_qty;
+            return;
+        }
+
+        // 反対方向は建玉を減らした分を確定し、超えた分は新しい建玉とする
+        let closed_qty = fill.qty.min(self.position.abs());
+        self.realized_pnl +=
+            closed_qty * (fill.price - self.average_price) * self.position.signum();
+        self.position += signed_qty;
+        if self.position.abs() < 1e-12 {
+            self.position = 0.0;
+            self.average_price = 0.0;
+        } else if closed_qty < fill.qty {
+            self.average_price = fill.price;
+        }
+    }
+
+    // 注文IDごとの累計約定数量及び平均約定価格から、前回からの差分を約定として記録する
+    // why: 約定は自己注文IDごとの累計でのみ取得でき、個々の約定を受信しない
+    pub fn record_cumulative(
+        &mut self,
+        order_id: &str,
+        side: OrderSide,
+        qty: f64,
+        average_price: f64,
+        is_maker: bool,
+    ) {
+        let (prev_qty, prev_notional) = self.cumulative.get(order_id).copied().unwrap_or_default();
+        let notional = qty * average_price;
+        if qty <= prev_qty || average_price <= 0.0 {
+            return;
+        }
+        self.cumulative
+            .insert(order_id.to_string(), (qty, notional));
+
+        let delta_qty = qty - prev_qty;
+        self.record(&Fill {
+            side,
+            qty: delta_qty,
+            price: (notional - prev_notional) / delta_qty,
+            is_maker,
+        });
+    }
+
+    // 前回の計上以降に増減した手数料控除後の確定損益を返し、計上済みとする
+    pub fn take_realized(&mut self) -> f64 {
+        let realized = self.realized_pnl - self.fees;
+        let delta = realized - self.booked_pnl;
+        self.booked_pnl = realized;
+        delta
+    }
+
+    pub fn summary(&self, ltp: f64) -> Summary {
+        let unrealized_pnl = if ltp > 0.0 {
+            self.position * (ltp - self.average_price)
+        } else {
+            0.0
+        };
+
+        Summary {
+            symbol: self.symbol.clone(),
+            net_position: self.position,
+            average_price: self.average_price,
+            realized_pnl: self.realized_pnl,
+            unrealized_pnl,
+            ltp,
+            fees: self.fees,
+            net_pnl: self.realized_pnl + unrealized_pnl - self.fees,
+            maker_volume: self.maker_volume,
+            taker_volume: self.taker_volume,
+            fills: self.fills,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(side: OrderSide, qty: f64, price: f64, is_maker: bool) -> Fill {
+        Fill {
+            side,
+            qty,
+            price,
+            is_maker,
+        }
+    }
+
+    #[test]
+    fn test_tracker_round_trip() {
+        let mut tracker = Tracker::new(
+            "BTCUSDT".to_string(),
+            FeeConfig {
+                maker_bps: -1.0,
+                taker_bps: 5.0,
+            },
+        );
+
+        tracker.record(&fill(OrderSide::Buy, 1.0, 100.0, true));
+        tracker.record(&fill(OrderSide::Buy, 1.0, 102.0, true));
+        let summary = tracker.summary(103.0);
+        assert_eq!(summary.average_price, 101.0);
+        assert_eq!(summary.unrealized_pnl, 4.0);
+
+        // 建玉を超える売りは反対方向の建玉になる
+        tracker.record(&fill(OrderSide::Sell, 3.0, 104.0, false));
+        let summary = tracker.summary(104.0);
+        assert_eq!(summary.realized_pnl, 6.0);
+        assert_eq!(summary.net_position, -1.0);
+        assert_eq!(summary.average_price, 104.0);
+        assert_eq!(summary.unrealized_pnl, 0.0);
+        // メイカー: 202 * -1bps, テイカー: 312 * 5bps
+        assert!((summary.fees - (-0.0202 + 0.156)).abs() < 1e-9);
+        assert_eq!(summary.maker_volume, 202.0);
+        assert_eq!(summary.taker_volume, 312.0);
+        assert_eq!(summary.fills, 3);
+    }
+
+    #[test]
+    fn test_tracker_record_cumulative() {
+        let mut tracker = Tracker::new("BTCUSDT".to_string(), FeeConfig::default());
+
+        tracker.record_cumulative("a", OrderSide::Sell, 1.0, 100.0, true);
+        // 累計2.0を平均101.0、差分1.0は102.0で約定
+        tracker.record_cumulative("a", OrderSide::Sell, 2.0, 101.0, true);
+        // 累計が増えなければ記録しない
+        tracker.record_cumulative("a", OrderSide::Sell, 2.0, 101.0, true);
+
+        let summary = tracker.summary(0.0);
+        assert_eq!(summary.net_position, -2.0);
+        assert_eq!(summary.average_price, 101.0);
+        assert_eq!(summary.fills, 2);
+        assert_eq!(summary.maker_volume, 202.0);
+    }
+
+    #[test]
+    fn test_tracker_take_realized() {
+        let mut tracker = Tracker::new(
+            "BTCUSDT".to_string(),
+            FeeConfig {
+                maker_bps: 0.0,
+                taker_bps: 10.0,
+            },
+        );
+
+        tracker.record(&fill(OrderSide::Buy, 1.0, 100.0, true));
+        assert_eq!(tracker.take_realized(), 0.0);
+        // 確定損益-2.0及び手数料0.098
+        tracker.record(&fill(OrderSide::Sell, 1.0, 98.0, false));
+        assert!((tracker.take_realized() + 2.098).abs() < 1e-9);
+        // 計上済みの分は返さない
+        assert_eq!(tracker.take_realized(), 0.0);
+    }
+}
@@ -32,21 +32,13 @@ pub async fn get_positions(
     }
 }
 
-// 指定した注文IDのポジションを集計する
-pub fn aggrigate_position(order_id: String, positions: Vec<Position>) -> Position {
-    let mut pos = Position::default();
-    if positions.is_empty() {
-        return pos;
-    }
-
-    for p in positions {
-        if order_id != p.order_id {
-            continue;
-        }
-
-        pos.qty += p.qty;
-        pos.price += p.price;
-    }
-
-    pos
+// 買い建玉を正、売り建玉を負とした建玉の合計
+pub fn net_position(positions: &[Position]) -> f64 {
+    positions
+        .iter()
+        .map(|p| match p.side.as_str() {
+            "Sell" | "sell" => -p.qty,
+            _ => p.qty,
+        })
+        .sum()
 }
@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use crate::funcs::client::Log;
+use crate::target::exchanges::models::{OpenOrder, ToExchange};
+use crate::target::order::{OrderInfo, OrphanPolicy};
+
+// 前回起動時の注文を整理する
+// 異常終了後の再起動では、前回の注文が取引所に残っている可能性がある
+// 自己注文IDの接頭辞が一致する未約定注文を対象に、設定に従い引き継ぐかキャンセルする
+// - Adopt: 各側の注文IDと一致する注文を1件ずつ引き継ぎ、残りはキャンセルする
+// - Cancel: 全件キャンセルする
+pub async fn reconcile_orphans(
+    exchange_client: &ToExchange,
+    legs: &[(String, Arc<Mutex<OrderInfo>>)],
+    policy: &OrphanPolicy,
+    order_link_prefix: &str,
+) -> Log {
+    let orders = match exchange_client.open_orders().await {
+        Ok(v) => v,
+        Err(e) => {
+            return Log {
+                level: "error".to_string(),
+                message: format!("[reconcile] open orders are not fetched: {}", e),
+                timestamp: chrono::Local::now().to_string(),
+            };
+        }
+    };
+
+    let orphans = orders
+        .into_iter()
+        .filter(|o| o.order_id.starts_with(order_link_prefix))
+        .collect::<Vec<OpenOrder>>();
+    if orphans.is_empty() {
+        return Log {
+            level: "info".to_string(),
+            message: "[reconcile] no orphaned orders".to_string(),
+            timestamp: chrono::Local::now().to_string(),
+        };
+    }
+
+    let total = orphans.len();
+    let mut adopted: Vec<OpenOrder> = vec![];
+    let mut cancelled = vec![];
+    let mut failed = vec![];
+    for orphan in orphans {
+        let is_adoptable = *policy == OrphanPolicy::Adopt
+            && !adopted.iter().any(|o| o.order_id == orphan.order_id);
+        if let Some((_, order_manage)) = legs
+            .iter()
+            .find(|(order_id, _)| is_adoptable && *order_id == orphan.order_id)
+        {
+            let mut w = order_manage.lock().await;
+            w.adopt(orphan.order_id.clone(), orphan.price);
+            adopted.push(orphan);
+            continue;
+        }
+
+        match exchange_client.cancel_order(orphan.order_id.clone()).await {
+            Ok(_) => cancelled.push(orphan.order_id),
+            Err(e) => failed.push(format!("{}: {}", orphan.order_id, e)),
+        }
+    }
+
+    Log {
+        level: if failed.is_empty() { "info" } else { "error" }.to_string(),
+        message: format!(
+            "[reconcile] orphaned orders: {}, adopted: {:?}, cancelled: {:?}, failed: {:?}",
+            total, adopted, cancelled, failed
+        ),
+        timestamp: chrono::Local::now().to_string(),
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::funcs::position;
+use crate::target::exchanges::models::{OrderParams, OrderSide, Position};
+
+// 発注前の上限設定
+// 各上限は0で無効
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Config {
+    // 発注後の建玉の絶対値の上限
+    #[serde(default)]
+    pub max_position: f64,
+    // 1注文の想定元本の上限
+    #[serde(default)]
+    pub max_order_notional: f64,
+    // 同じRunner内で板に出す注文数の上限
+    #[serde(default)]
+    pub max_open_orders: usize,
+    // 銘柄ごとの当日の確定損失の上限、正の値で指定する
+    #[serde(default)]
+    pub max_daily_loss: f64,
+    // 口座全体の当日の確定損失の上限、正の値で指定する
+    #[serde(default)]
+    pub max_account_daily_loss: f64,
+}
+
+// 上限に抵触した注文の記録
+// ログには JSON として出力する
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskEvent {
+    pub rule: Rule,
+    pub symbol: String,
+    pub side: OrderSide,
+    pub qty: f64,
+    pub price: f64,
+    // 抵触した値と上限
+    pub value: f64,
+    pub limit: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Rule {
+    #[serde(rename = "max_position")]
+    Position,
+    #[serde(rename = "max_order_notional")]
+    OrderNotional,
+    #[serde(rename = "max_open_orders")]
+    OpenOrders,
+    #[serde(rename = "max_daily_loss")]
+    DailyLoss,
+    #[serde(rename = "max_account_daily_loss")]
+    AccountDailyLoss,
+}
+
+// 発注時点の状態
+pub struct Exposure {
+    // 売買方向を符号とした建玉
+    pub net_position: f64,
+    // 自身以外の板に出している注文数
+    pub open_orders: usize,
+    // 成行注文の想定元本に使用する価格
+    pub ltp: f64,
+}
+
+impl Exposure {
+    // 取引所の建玉から算出する
+    pub fn from_positions(positions: &[Position], open_orders: usize, ltp: f64) -> Self {
+        Exposure {
+            net_position: position::net_position(positions),
+            open_orders,
+            ltp,
+        }
+    }
+}
+
+impl Config {
+    // 上限に抵触する場合は最初のイベントを返す
+    // 建玉を減らす注文は損失の上限によらず許可する
+    pub fn check(
+        &self,
+        symbol: &str,
+        params: &OrderParams,
+        exposure: &Exposure,
+        ledger: &Ledger,
+    ) -> Option<RiskEvent> {
+        let price = if params.price > 0.0 {
+            params.price
+        } else {
+            exposure.ltp
+        };
+        let event = |rule: Rule, value: f64, limit: f64| RiskEvent {
+            rule,
+            symbol: symbol.to_string(),
+            side: params.side.clone(),
+            qty: params.qty,
+            price,
+            value,
+            limit,
+        };
+
+        let signed_qty = match params.side {
+            OrderSide::Buy => params.qty,
+            OrderSide::Sell => -params.qty,
+        };
+        let after = exposure.net_position + signed_qty;
+        let is_reducing = after.abs() <= exposure.net_position.abs();
+
+        if self.max_position > 0.0 && !is_reducing && after.abs() > self.max_position {
+            return Some(event(Rule::Position, after.abs(), self.max_position));
+        }
+
+        let notional = price * params.qty;
+        if self.max_order_notional > 0.0 && notional > self.max_order_notional {
+            return Some(event(
+                Rule::OrderNotional,
+                notional,
+                self.max_order_notional,
+            ));
+        }
+
+        if self.max_open_orders > 0 && exposure.open_orders + 1 > self.max_open_orders {
+            return Some(event(
+                Rule::OpenOrders,
+                (exposure.open_orders + 1) as f64,
+                self.max_open_orders as f64,
+            ));
+        }
+
+        if is_reducing {
+            return None;
+        }
+
+        let loss = -ledger.daily_pnl(symbol);
+        if self.max_daily_loss > 0.0 && loss >= self.max_daily_loss {
+            return Some(event(Rule::DailyLoss, loss, self.max_daily_loss));
+        }
+
+        let account_loss = -ledger.account_daily_pnl();
+        if self.max_account_daily_loss > 0.0 && account_loss >= self.max_account_daily_loss {
+            return Some(event(
+                Rule::AccountDailyLoss,
+                account_loss,
+                self.max_account_daily_loss,
+            ));
+        }
+
+        None
+    }
+}
+
+// 当日の確定損益
+// Runnerの再起動をまたいで保持するため、AppStateで管理する
+#[derive(Debug, Default, Clone)]
+pub struct Ledger {
+    date: Option<NaiveDate>,
+    pnl: HashMap<String, f64>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Ledger::default()
+    }
+
+    // 日付が変わっていれば集計をやり直す
+    pub fn record(&mut self, symbol: &str, pnl: f64, now: DateTime<Utc>) {
+        let today = now.date_naive();
+        if self.date != Some(today) {
+            self.date = Some(today);
+            self.pnl.clear();
+        }
+
+        *self.pnl.entry(symbol.to_string()).or_default() += pnl;
+    }
+
+    pub fn daily_pnl(&self, symbol: &str) -> f64 {
+        if !self.is_today() {
+            return 0.0;
+        }
+        self.pnl.get(symbol).copied().unwrap_or_default()
+    }
+
+    pub fn account_daily_pnl(&self) -> f64 {
+        if !self.is_today() {
+            return 0.0;
+        }
+        self.pnl.values().sum()
+    }
+
+    fn is_today(&self) -> bool {
+        self.date == Some(Utc::now().date_naive())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::target::exchanges::models::{OrderType, TimeInForce};
+
+    fn params(side: OrderSide, price: f64, qty: f64) -> OrderParams {
+        OrderParams {
+            order_id: None,
+            side,
+            price,
+            qty,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::Gtc,
+            reduce_only: false,
+        }
+    }
+
+    fn exposure(net_position: f64) -> Exposure {
+        Exposure {
+            net_position,
+            open_orders: 0,
+            ltp: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_check_position_and_notional() {
+        let config = Config {
+            max_position: 1.0,
+            max_order_notional: 150.0,
+            ..Default::default()
+        };
+        let ledger = Ledger::new();
+
+        assert_eq!(
+            config.check(
+                "BTCUSDT",
+                &params(OrderSide::Buy, 100.0, 1.0),
+                &exposure(0.0),
+                &ledger
+            ),
+            None
+        );
+        let event = config
+            .check(
+                "BTCUSDT",
+                &params(OrderSide::Buy, 100.0, 0.5),
+                &exposure(0.8),
+                &ledger,
+            )
+            .unwrap();
+        assert_eq!(event.rule, Rule::Position);
+        // 建玉を減らす注文は許可する
+        assert_eq!(
+            config.check(
+                "BTCUSDT",
+                &params(OrderSide::Sell, 100.0, 0.5),
+                &exposure(1.2),
+                &ledger
+            ),
+            None
+        );
+        // 成行注文は最終約定価格で想定元本を算出する
+        let event = config
+            .check(
+                "BTCUSDT",
+                &params(OrderSide::Sell, 0.0, 2.0),
+                &exposure(2.0),
+                &ledger,
+            )
+            .unwrap();
+        assert_eq!(event.rule, Rule::OrderNotional);
+        assert_eq!(event.value, 200.0);
+    }
+
+    #[test]
+    fn test_check_open_orders() {
+        let config = Config {
+            max_open_orders: 2,
+            ..Default::default()
+        };
+        let ledger = Ledger::new();
+        let mut state = exposure(0.0);
+
+        state.open_orders = 1;
+        assert_eq!(
+            config.check(
+                "BTCUSDT",
+                &params(OrderSide::Buy, 100.0, 1.0),
+                &state,
+                &ledger
+            ),
+            None
+        );
+        state.open_orders = 2;
+        let event = config
+            .check(
+                "BTCUSDT",
+                &params(OrderSide::Buy, 100.0, 1.0),
+                &state,
+                &ledger,
+            )
+            .unwrap();
+        assert_eq!(event.rule, Rule::OpenOrders);
+    }
+
+    #[test]
+    fn test_check_daily_loss() {
+        let config = Config {
+            max_daily_loss: 50.0,
+            max_account_daily_loss: 80.0,
+            ..Default::default()
+        };
+        let mut ledger = Ledger::new();
+        ledger.record("BTCUSDT", -30.0, Utc::now());
+        ledger.record("ETHUSDT", -60.0, Utc::now());
+
+        let event = config
+            .check(
+                "ETHUSDT",
+                &params(OrderSide::Buy, 100.0, 1.0),
+                &exposure(0.0),
+                &ledger,
+            )
+            .unwrap();
+        assert_eq!(event.rule, Rule::DailyLoss);
+        let event = config
+            .check(
+                "BTCUSDT",
+                &params(OrderSide::Buy, 100.0, 1.0),
+                &exposure(0.0),
+                &ledger,
+            )
+            .unwrap();
+        assert_eq!(event.rule, Rule::AccountDailyLoss);
+        // 決済注文は許可する
+        assert_eq!(
+            config.check(
+                "BTCUSDT",
+                &params(OrderSide::Sell, 100.0, 1.0),
+                &exposure(1.0),
+                &ledger
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_ledger_resets_by_date() {
+        let mut ledger = Ledger::new();
+        let yesterday = Utc::now() - chrono::Duration::days(1);
+        ledger.record("BTCUSDT", -30.0, yesterday);
+        assert_eq!(ledger.daily_pnl("BTCUSDT"), 0.0);
+
+        ledger.record("BTCUSDT", 10.0, Utc::now());
+        assert_eq!(ledger.daily_pnl("BTCUSDT"), 10.0);
+        assert_eq!(ledger.account_daily_pnl(), 10.0);
+    }
+}
@@ -0,0 +1,193 @@
+use chrono::{DateTime, Datelike, NaiveTime, Timelike, Utc, Weekday};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+// 取引時間帯の設定
+// 時間帯内であればRunnerを起動し、時間帯外または停止期間に入ればRunnerを停止する
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Schedule {
+    // IANAタイムゾーン名、例: Asia/Tokyo
+    pub timezone: String,
+    // 週次の取引時間帯
+    pub windows: Vec<Window>,
+    // 取引時間帯内でも停止する期間
+    #[serde(default)]
+    pub blackouts: Vec<Blackout>,
+}
+
+// 曜日と開始・終了時刻
+// 終了時刻が開始時刻以前であれば翌日の終了時刻までとする
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Window {
+    pub weekday: Weekday,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Blackout {
+    // 指標発表など一度限りの期間
+    #[serde(rename = "period")]
+    Period {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+    // 資金調達時刻など毎日同じ時刻の前後
+    #[serde(rename = "daily")]
+    Daily {
+        at: NaiveTime,
+        before_sec: i64,
+        after_sec: i64,
+    },
+}
+
+const SECONDS_PER_DAY: i64 = 86_400;
+
+impl Schedule {
+    pub fn tz(&self) -> Result<Tz, String> {
+        self.timezone
+            .parse::<Tz>()
+            .map_err(|e| format!("invalid timezone: {}, {}", self.timezone, e))
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.tz().is_ok() && !self.windows.is_empty()
+    }
+
+    // 指定時刻が取引時間帯内かつ停止期間外であればtrue
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        let tz = match self.tz() {
+            Ok(v) => v,
+            Err(_) => return false,
+        };
+        let local = now.with_timezone(&tz);
+
+        self.windows
+            .iter()
+            .any(|w| w.contains(local.weekday(), local.time()))
+            && !self.blackouts.iter().any(|b| b.contains(now, local.time()))
+    }
+}
+
+impl Window {
+    fn contains(&self, weekday: Weekday, time: NaiveTime) -> bool {
+        if self.start < self.end {
+            return self.weekday == weekday && self.start <= time && time < self.end;
+        }
+
+        // 日を跨ぐ時間帯
+        (self.weekday == weekday && self.start <= time)
+            || (self.weekday.succ() == weekday && time < self.end)
+    }
+}
+
+impl Blackout {
+    fn contains(&self, now: DateTime<Utc>, local_time: NaiveTime) -> bool {
+        match self {
+            Blackout::Period { start, end } => *start <= now && now < *end,
+            Blackout::Daily {
+                at,
+                before_sec,
+                after_sec,
+            } => {
+                // 基準時刻からの経過秒を[-12h, 12h)に正規化して日跨ぎを扱う
+                let diff = (local_time.num_seconds_from_midnight() as i64
+                    - at.num_seconds_from_midnight() as i64)
+                    .rem_euclid(SECONDS_PER_DAY);
+                let diff = if diff >= SECONDS_PER_DAY / 2 {
+                    diff - SECONDS_PER_DAY
+                } else {
+                    diff
+                };
+
+                -before_sec <= diff && diff < *after_sec
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn schedule() -> Schedule {
+        Schedule {
+            timezone: "Asia/Tokyo".to_string(),
+            windows: vec![
+                Window {
+                    weekday: Weekday::Mon,
+                    start: time(9, 0),
+                    end: time(15, 0),
+                },
+                Window {
+                    weekday: Weekday::Fri,
+                    start: time(22, 0),
+                    end: time(2, 0),
+                },
+            ],
+            blackouts: vec![Blackout::Daily {
+                at: time(9, 0),
+                before_sec: 300,
+                after_sec: 600,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_schedule_is_active() {
+        let s = schedule();
+        // 2025-01-06は月曜日、JST = UTC+9
+        let jst = |d: u32, h: u32, m: u32| {
+            chrono_tz::Asia::Tokyo
+                .with_ymd_and_hms(2025, 1, d, h, m, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+
+        assert!(!s.is_active(jst(6, 8, 59)));
+        // 09:00-09:10は停止期間
+        assert!(!s.is_active(jst(6, 9, 5)));
+        assert!(s.is_active(jst(6, 9, 10)));
+        assert!(s.is_active(jst(6, 14, 59)));
+        assert!(!s.is_active(jst(6, 15, 0)));
+        // 火曜日は時間帯外
+        assert!(!s.is_active(jst(7, 10, 0)));
+        // 金曜日22:00から土曜日02:00まで
+        assert!(s.is_active(jst(10, 23, 0)));
+        assert!(s.is_active(jst(11, 1, 59)));
+        assert!(!s.is_active(jst(11, 2, 0)));
+    }
+
+    #[test]
+    fn test_blackout_period() {
+        let mut s = schedule();
+        let start = Utc.with_ymd_and_hms(2025, 1, 6, 3, 0, 0).unwrap();
+        s.blackouts = vec![Blackout::Period {
+            start,
+            end: start + chrono::Duration::minutes(30),
+        }];
+
+        assert!(!s.is_active(start + chrono::Duration::minutes(10)));
+        assert!(s.is_active(start + chrono::Duration::minutes(30)));
+    }
+
+    #[test]
+    fn test_daily_blackout_across_midnight() {
+        let b = Blackout::Daily {
+            at: time(0, 0),
+            before_sec: 600,
+            after_sec: 300,
+        };
+
+        assert!(b.contains(Utc::now(), time(23, 55)));
+        assert!(b.contains(Utc::now(), time(0, 4)));
+        assert!(!b.contains(Utc::now(), time(0, 5)));
+        assert!(!b.contains(Utc::now(), time(23, 49)));
+    }
+}
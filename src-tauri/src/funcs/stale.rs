@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+// 板またはTickerの更新が途絶えた場合に注文を取り下げる設定
+// 各上限は0で無効
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Config {
+    // 板の更新がない期間の上限
+    #[serde(default)]
+    pub book_timeout_ms: u64,
+    // Tickerの更新がない期間の上限
+    #[serde(default)]
+    pub ticker_timeout_ms: u64,
+}
+
+impl Config {
+    pub fn is_ok(&self) -> bool {
+        self.book_timeout_ms > 0 || self.ticker_timeout_ms > 0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    // いずれかの更新が途絶えた
+    Stale { reason: String },
+    // 全ての更新が再開した
+    Resume,
+}
+
+// 最終更新からの経過時間で停滞を検出する
+// 経過時間は呼び出し側から受け取り、単体で検証できるようにする
+pub struct Monitor {
+    config: Config,
+    is_stale: bool,
+}
+
+impl Monitor {
+    pub fn new(config: Config) -> Self {
+        Monitor {
+            config,
+            is_stale: false,
+        }
+    }
+
+    // 停滞・再開の境界であればイベントを返す
+    pub fn check(&mut self, book_elapsed: Duration, ticker_elapsed: Duration) -> Option<Event> {
+        let reason = self.reason(book_elapsed, ticker_elapsed);
+        match (reason, self.is_stale) {
+            (Some(reason), false) => {
+                self.is_stale = true;
+                Some(Event::Stale { reason })
+            }
+            (None, true) => {
+                self.is_stale = false;
+                Some(Event::Resume)
+            }
+            _ => None,
+        }
+    }
+
+    fn reason(&self, book_elapsed: Duration, ticker_elapsed: Duration) -> Option<String> {
+        let is_over = |elapsed: Duration, timeout_ms: u64| {
+            timeout_ms > 0 && elapsed > Duration::from_millis(timeout_ms)
+        };
+
+        if is_over(book_elapsed, self.config.book_timeout_ms) {
+            return Some(format!(
+                "orderboard has not updated for {:?} > {}ms",
+                book_elapsed, self.config.book_timeout_ms
+            ));
+        }
+        if is_over(ticker_elapsed, self.config.ticker_timeout_ms) {
+            return Some(format!(
+                "ticker has not updated for {:?} > {}ms",
+                ticker_elapsed, self.config.ticker_timeout_ms
+            ));
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_monitor_stale_and_resume() {
+        let mut monitor = Monitor::new(Config {
+            book_timeout_ms: 1_000,
+            ticker_timeout_ms: 3_000,
+        });
+        let ms = Duration::from_millis;
+
+        assert_eq!(monitor.check(ms(500), ms(500)), None);
+        assert!(matches!(
+            monitor.check(ms(1_500), ms(500)),
+            Some(Event::Stale { .. })
+        ));
+        // 停滞中は再度通知しない
+        assert_eq!(monitor.check(ms(2_000), ms(3_500)), None);
+        // 板が再開してもTickerが途絶えていれば停滞を継続する
+        assert_eq!(monitor.check(ms(100), ms(3_500)), None);
+        assert_eq!(monitor.check(ms(100), ms(100)), Some(Event::Resume));
+    }
+
+    #[test]
+    fn test_monitor_disabled_timeout() {
+        let mut monitor = Monitor::new(Config {
+            book_timeout_ms: 0,
+            ticker_timeout_ms: 1_000,
+        });
+        let ms = Duration::from_millis;
+
+        // 0の項目は判定しない
+        assert_eq!(monitor.check(ms(60_000), ms(500)), None);
+        assert!(matches!(
+            monitor.check(ms(0), ms(1_500)),
+            Some(Event::Stale { .. })
+        ));
+    }
+}
@@ -0,0 +1,499 @@
+use std::sync::Arc;
+
+use log::info;
+
+use crate::{
+    board::{self, book::Orderboard},
+    target::{
+        exchanges::models::{OrderParams, OrderSide, Ticker, TimeInForce},
+        order::{self, ChaseAction, OrderInfo, Placement, StrategyKind},
+    },
+};
+
+// 戦略に渡す現在の状態
+// 更新通知の種類によらず、注文タスクが保持する最新の値を渡す
+pub struct State<'a> {
+    pub order: &'a OrderInfo,
+    pub ticker: &'a Ticker,
+    // 買い建玉を正とした取引所の建玉
+    pub net_position: f64,
+}
+
+// 戦略が出力する注文意図
+// 注文タスクが先頭から順に実行する
+#[derive(Debug, Clone)]
+pub enum Intent {
+    // 新規注文、filled_qtyは注文時点の約定済み数量
+    Place {
+        params: OrderParams,
+        filled_qty: f64,
+    },
+    // 注文価格及び数量の変更
+    Amend {
+        order_id: String,
+        price: f64,
+        qty: f64,
+    },
+    Cancel {
+        order_id: String,
+    },
+    // 指定数量の約定が完了した
+    Complete {
+        order_id: String,
+        filled_qty: f64,
+        average_price: f64,
+    },
+    // Runnerを停止する
+    Stop {
+        reason: String,
+    },
+    Log {
+        level: String,
+        message: String,
+    },
+}
+
+// 板・Ticker・建玉・注文状態の更新を受けて注文意図を出力する
+// 取引所との通信やチャネルを持たないため、単体で検証できる
+pub trait Strategy: Send {
+    // 板の更新、own_ordersは同じRunner内の全ての自己注文の価格及び数量
+    fn on_book(
+        &mut self,
+        board: &Orderboard,
+        own_orders: &[(f64, f64)],
+        state: &State,
+    ) -> Vec<Intent>;
+
+    // Tickerの更新
+    fn on_ticker(&mut self, _state: &State) -> Vec<Intent> {
+        vec![]
+    }
+
+    // 建玉の更新
+    fn on_position(&mut self, _state: &State) -> Vec<Intent> {
+        vec![]
+    }
+
+    // 注文・キャンセル後の注文状態
+    fn on_order(&mut self, _order: &OrderInfo) {}
+}
+
+// 注文設定に従って戦略を生成する
+pub fn build(
+    board_config: Arc<board::filter::Config>,
+    order_config: order::Config,
+    order_link_id: String,
+    rank: usize,
+    max_position: Option<f64>,
+) -> Box<dyn Strategy> {
+    match order_config.strategy {
+        StrategyKind::WallFollow => Box::new(WallFollow::new(
+            board_config,
+            order_config,
+            order_link_id,
+            rank,
+            max_position,
+        )),
+    }
+}
+
+// 条件を満たす壁の前に注文し、壁の移動に追従する
+pub struct WallFollow {
+    board_config: Arc<board::filter::Config>,
+    order_config: order::Config,
+    order_link_id: String,
+    // 上位の壁に分散する場合の順位、0が最良価格側の壁
+    rank: usize,
+    // 両側で追従する場合に共有する建玉の上限
+    max_position: Option<f64>,
+    // 追従距離の上限による一時停止中か
+    is_chase_paused: bool,
+}
+
+impl WallFollow {
+    pub fn new(
+        board_config: Arc<board::filter::Config>,
+        order_config: order::Config,
+        order_link_id: String,
+        rank: usize,
+        max_position: Option<f64>,
+    ) -> Self {
+        WallFollow {
+            board_config,
+            order_config,
+            order_link_id,
+            rank,
+            max_position,
+            is_chase_paused: false,
+        }
+    }
+
+    // 対象の板を検出する
+    // - 指定価格内
+    // - 指定サイズ以上
+    // - 自己注文価格以外
+    // 壁に並ぶ場合は自己注文の価格を除外せず、自己注文の数量を差し引いて判定する
+    // 順位の壁が見つからない場合は追従しない
+    fn target_price(&self, board: &Orderboard, own_orders: &[(f64, f64)]) -> Option<f64> {
+        let walls = match self.order_config.placement {
+            Placement::Join => {
+                board.target_books_joined(&self.board_config, self.rank + 1, own_orders)
+            }
+            _ => {
+                let own_prices = own_orders
+                    .iter()
+                    .map(|(price, _)| *price)
+                    .collect::<Vec<f64>>();
+                board.target_books(&self.board_config, self.rank + 1, &own_prices)
+            }
+        };
+
+        walls.get(self.rank).copied()
+    }
+}
+
+impl Strategy for WallFollow {
+    fn on_book(
+        &mut self,
+        board: &Orderboard,
+        own_orders: &[(f64, f64)],
+        state: &State,
+    ) -> Vec<Intent> {
+        let order_config = &self.order_config;
+        // 先注文があれば、部分約定の可能性がある
+        // 注文タスクが自己注文IDで照会した側の累計約定数量を差し引いた数量を使用する
+        // why: 建玉の情報には自己注文IDが含まれず、建玉から側ごとの約定を識別できない
+        let filled = state.order.executed_qty;
+
+        // 指定枚数以上の約定を確認した場合は完了とする
+        if let Some(order_id) = state.order.order_id.clone() {
+            if state.order.qty - filled <= 0.0 {
+                return vec![Intent::Complete {
+                    average_price: state.order.average_price(),
+                    order_id,
+                    filled_qty: filled,
+                }];
+            }
+        }
+
+        // 即時執行の注文は1回のみ送信し、約定の照合は注文タスクが行う
+        // why: 板に残らない注文を再注文すると、壁の移動ごとに約定を重ねる
+        if order_config.is_immediate() && state.order.order_id.is_some() {
+            return vec![];
+        }
+
+        let target_price = match self.target_price(board, own_orders) {
+            Some(v) => v,
+            None => return vec![],
+        };
+
+        // - placement_price: 対象価格に対してplacementに従い注文価格を算出する
+        // why: 壁の前・同値・後ろのいずれに置くかで約定のしやすさと優先度が変わる
+        // 設定型にはticker baseのティックサイズは入っている
+        // - escalate: 初回注文から指定秒数経過後は壁の価格でIOC注文を行う
+        // why: 期限までに建玉を作り切る必要がある場合、板の前に並び続けるより約定を優先する
+        let (target_price, time_in_force) =
+            if state.order.is_escalated(order_config.escalate_after_sec) {
+                info!("order escalated to IOC at wall price: {}", target_price);
+                (target_price, TimeInForce::Ioc)
+            } else {
+                (
+                    order_config.placement_price(target_price),
+                    order_config.time_in_force(),
+                )
+            };
+
+        // 板に出している数量が全て約定していれば、同じ価格でも補充する
+        // 約定済みの注文は取引所に存在しないため、キャンセルしない
+        let is_working_filled = state.order.is_working_filled(filled);
+
+        // 自己注文の価格と同値であれば、キャンセル・再注文しない
+        if !is_working_filled && state.order.price.unwrap_or_default() == target_price {
+            info!("order and target_price are same price: {}", target_price);
+            return vec![];
+        }
+
+        // - chase: 追従先が初回注文価格、最終約定価格、反対側の最良価格から離れすぎていれば再注文しない
+        // why: 静的な価格範囲のみでは、相場から離れた壁まで追従してしまう
+        let best_opposite = match order_config.side {
+            OrderSide::Buy => state.ticker.best_ask,
+            OrderSide::Sell => state.ticker.best_bid,
+        };
+        let mut intents = vec![];
+        match order_config.chase.check(
+            target_price,
+            state.order.first_price,
+            state.ticker.ltp,
+            best_opposite,
+        ) {
+            Some(reason) => {
+                if order_config.chase.action == ChaseAction::Stop {
+                    return vec![Intent::Stop {
+                        reason: format!("chase guard: {}", reason),
+                    }];
+                }
+                // 一時停止中は同じ理由のログを繰り返さない
+                if self.is_chase_paused {
+                    return vec![];
+                }
+                self.is_chase_paused = true;
+                return vec![Intent::Log {
+                    level: "error".to_string(),
+                    message: format!("[chase] pause re-quoting: {}", reason),
+                }];
+            }
+            None if self.is_chase_paused => {
+                self.is_chase_paused = false;
+                intents.push(Intent::Log {
+                    level: "info".to_string(),
+                    message: format!("[chase] resume re-quoting at price: {}", target_price),
+                });
+            }
+            None => (),
+        }
+
+        // - cancel: order_idがある場合、キャンセルする
+        // 取り下げ済みの注文は価格を持たないため、キャンセルしない
+        if let Some(order_id) = state
+            .order
+            .order_id
+            .clone()
+            .filter(|_| !is_working_filled && state.order.price.is_some())
+        {
+            intents.push(Intent::Cancel { order_id });
+        }
+
+        // 両側で追従する場合、建玉の上限を超えない数量に制限する
+        // why: 片側のみ約定が進むと、意図しない方向の建玉が積み上がる
+        let remaining_qty = state.order.qty - filled;
+        let remaining_qty = match self.max_position {
+            Some(max_position) => {
+                let net = state.net_position;
+                let capacity = match order_config.side {
+                    OrderSide::Buy => max_position - net,
+                    OrderSide::Sell => max_position + net,
+                };
+                if capacity <= 0.0 {
+                    info!(
+                        "position limit reached: net: {}, max: {}",
+                        net, max_position
+                    );
+                    return intents;
+                }
+                remaining_qty.min(capacity)
+            }
+            None => remaining_qty,
+        };
+
+        // - clip_qty: 板に見せる数量を小分けにする
+        // why: 全数量を板に出すと、こちらの意図が他の参加者に見えてしまう
+        let qty = order_config.clip_qty(remaining_qty);
+
+        // - order: 新規注文または再注文を行う
+        intents.push(Intent::Place {
+            params: OrderParams {
+                // 同じ注文IDを使用する
+                order_id: Some(self.order_link_id.clone()),
+                side: order_config.side.clone(),
+                price: target_price,
+                qty,
+                order_type: order_config.order_type.clone(),
+                time_in_force,
+                reduce_only: order_config.reduce_only,
+            },
+            filled_qty: filled,
+        });
+
+        intents
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::book::Book;
+    use crate::target::exchanges::models::{BookSide, OrderType};
+
+    fn setup() -> (WallFollow, Orderboard) {
+        let board_config = board::filter::Config {
+            side: BookSide::Bid,
+            hight: 200.0,
+            low: 0.0,
+            size: 1.0,
+        };
+        let mut order_config = order::Config::new("BTCUSDT".to_string(), 1.0, OrderSide::Buy);
+        order_config.tick_size = 0.5;
+
+        let board = Orderboard::new();
+        board.replace_bid(vec![
+            Book::new(0.5, 100.0),
+            Book::new(3.0, 99.0),
+            Book::new(0.5, 98.0),
+            Book::new(2.0, 97.0),
+        ]);
+
+        let strategy = WallFollow::new(
+            Arc::new(board_config),
+            order_config,
+            "link".to_string(),
+            0,
+            None,
+        );
+        (strategy, board)
+    }
+
+    #[test]
+    fn test_wall_follow_places_in_front_of_wall() {
+        let (mut strategy, board) = setup();
+        let order = strategy.order_config.to_order_info();
+        let ticker = Ticker::default();
+        let state = State {
+            order: &order,
+            ticker: &ticker,
+            net_position: 0.0,
+        };
+
+        let intents = strategy.on_book(&board, &[], &state);
+        assert_eq!(intents.len(), 1);
+        match &intents[0] {
+            Intent::Place { params, .. } => {
+                assert_eq!(params.price, 99.5);
+                assert_eq!(params.qty, 1.0);
+            }
+            v => panic!("unexpected intent: {:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_wall_follow_requotes_and_completes() {
+        let (mut strategy, board) = setup();
+        let mut order = strategy.order_config.to_order_info();
+        order.set_order("link".to_string(), 97.5);
+        let ticker = Ticker::default();
+
+        // 部分約定分を差し引いて再注文する
+        order.apply_execution(0.4, 97.5);
+        let state = State {
+            order: &order,
+            ticker: &ticker,
+            net_position: 0.0,
+        };
+        let intents = strategy.on_book(&board, &[(97.5, 1.0)], &state);
+        assert!(matches!(&intents[0], Intent::Cancel { order_id } if order_id == "link"));
+        match &intents[1] {
+            Intent::Place { params, filled_qty } => {
+                assert_eq!(params.price, 99.5);
+                assert!((params.qty - 0.6).abs() < 1e-9);
+                assert_eq!(*filled_qty, 0.4);
+            }
+            v => panic!("unexpected intent: {:?}", v),
+        }
+
+        // 全て約定していれば完了とする
+        order.apply_execution(1.0, 97.5);
+        let state = State {
+            order: &order,
+            ticker: &ticker,
+            net_position: 0.0,
+        };
+        let intents = strategy.on_book(&board, &[(97.5, 1.0)], &state);
+        assert!(matches!(
+            &intents[..],
+            [Intent::Complete { filled_qty, average_price, .. }] if *filled_qty == 1.0 && *average_price == 97.5
+        ));
+    }
+
+    #[test]
+    fn test_wall_follow_replenishes_filled_clip() {
+        let (mut strategy, board) = setup();
+        strategy.order_config.clip_size = 0.3;
+        let mut order = strategy.order_config.to_order_info();
+        order.set_order("link".to_string(), 99.5);
+        order.set_progress(0.3, 0.0);
+        let ticker = Ticker::default();
+
+        // 板に出した数量が全て約定していれば、同じ価格でもキャンセルせずに補充する
+        order.apply_execution(0.3, 99.5);
+        let state = State {
+            order: &order,
+            ticker: &ticker,
+            net_position: 0.0,
+        };
+        match &strategy.on_book(&board, &[(99.5, 0.3)], &state)[..] {
+            [Intent::Place { params, filled_qty }] => {
+                assert_eq!(params.price, 99.5);
+                assert!((params.qty - 0.3).abs() < 1e-9);
+                assert!((*filled_qty - 0.3).abs() < 1e-9);
+            }
+            v => panic!("unexpected intents: {:?}", v),
+        }
+    }
+
+    #[test]
+    fn test_wall_follow_limits_by_net_position() {
+        let (mut strategy, board) = setup();
+        strategy.max_position = Some(1.0);
+        let order = strategy.order_config.to_order_info();
+        let ticker = Ticker::default();
+
+        // 取引所の建玉から上限までの数量に制限する
+        let state = State {
+            order: &order,
+            ticker: &ticker,
+            net_position: 0.8,
+        };
+        match &strategy.on_book(&board, &[], &state)[..] {
+            [Intent::Place { params, .. }] => assert!((params.qty - 0.2).abs() < 1e-9),
+            v => panic!("unexpected intents: {:?}", v),
+        }
+
+        let state = State {
+            order: &order,
+            ticker: &ticker,
+            net_position: 1.0,
+        };
+        assert!(strategy.on_book(&board, &[], &state).is_empty());
+    }
+
+    #[test]
+    fn test_wall_follow_skips_same_price() {
+        let (mut strategy, board) = setup();
+        let mut order = strategy.order_config.to_order_info();
+        order.set_order("link".to_string(), 99.5);
+        let ticker = Ticker::default();
+        let state = State {
+            order: &order,
+            ticker: &ticker,
+            net_position: 0.0,
+        };
+
+        assert!(strategy.on_book(&board, &[(99.5, 1.0)], &state).is_empty());
+    }
+
+    #[test]
+    fn test_wall_follow_sends_market_order_once() {
+        let (mut strategy, board) = setup();
+        strategy.order_config.order_type = OrderType::Market;
+        let mut order = strategy.order_config.to_order_info();
+        let ticker = Ticker::default();
+
+        let state = State {
+            order: &order,
+            ticker: &ticker,
+            net_position: 0.0,
+        };
+        assert!(matches!(
+            &strategy.on_book(&board, &[], &state)[..],
+            [Intent::Place { .. }]
+        ));
+
+        // 送信後は壁が移動しても再注文しない
+        order.set_order("link".to_string(), 99.5);
+        let state = State {
+            order: &order,
+            ticker: &ticker,
+            net_position: 0.0,
+        };
+        assert!(strategy.on_book(&board, &[(97.5, 1.0)], &state).is_empty());
+    }
+}
@@ -1,59 +1,177 @@
 use core::panic;
+use std::collections::{HashSet, VecDeque};
 use std::env;
-use std::future::pending;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use log::{ info, log_enabled, trace};
-use tokio::sync::{broadcast, mpsc, Mutex, RwLock};
+use tokio::sync::{broadcast, mpsc, watch, Mutex, RwLock};
 use tokio::task::{spawn, JoinError, JoinHandle};
+use tokio_util::sync::CancellationToken;
 
 use crate::board;
 use crate::funcs::client::{Controller, Log, Logger};
-use crate::funcs::position;
+use crate::funcs::breaker::{self, Breaker};
+use crate::funcs::hedge::Hedger;
+use crate::funcs::strategy::{self, Intent, State, Strategy};
+use crate::funcs::{pnl, position, reconcile, risk, stale};
 use crate::target::exchanges::models::{
-    BookSide, DataType, OrderParams, Orderboard, Position, Ticker, ToExchange
+    BookSide, DataType, ExchangeError, Execution, OrderParams, OrderSide, OrderType, Orderboard, Position, Ticker, TimeInForce, ToExchange
 };
+use crate::target::exchange::ORDERBOARD_DEPTH;
+use crate::target::exchanges::connection;
+use crate::target::order::{self, ExitPlan, OrderInfo, Quote};
+
+// 停止命令を受けてから注文キャンセルの完了を待つ上限
+pub const STOP_CANCEL_TIMEOUT: Duration = Duration::from_secs(5);
+// 即時執行の注文の約定結果の確認間隔及び回数
+const SETTLE_INTERVAL: Duration = Duration::from_millis(200);
+const SETTLE_MAX_RETRY: usize = 10;
+// 板に出している注文の約定の照会間隔
+const EXECUTION_INTERVAL: Duration = Duration::from_secs(1);
+// 板の異常が解消しない場合に再取得を依頼する間隔
+const RESNAPSHOT_INTERVAL: Duration = Duration::from_secs(5);
+
+// Runner外から停止命令の送信及び状態の参照を行うための共有データ
+#[derive(Debug, Clone)]
+pub struct RunnerContext {
+    // 停止命令、Runner内の全タスクへ伝播する
+    pub cancel_handle: CancellationToken,
+    // 各側の注文タスクの終了通知、注文タスクの終了時に必ず発火する
+    pub finished: Vec<CancellationToken>,
+    // 各側の注文状態、先頭が主側
+    pub orders: Vec<Arc<Mutex<OrderInfo>>>,
+    pub positions: Arc<RwLock<Vec<Position>>>,
+    // 起動中の損益及び手数料、未実現損益はTickerの最終約定価格で評価する
+    pub pnl: Arc<RwLock<pnl::Tracker>>,
+    pub rx_ticker: watch::Receiver<Ticker>,
+}
+
+// 片側の追従に使用するデータ
+// 板の探索タスク、注文タスク、監視タスクで共有する
+#[derive(Debug, Clone)]
+struct OrderLeg {
+    board_config: Arc<board::filter::Config>,
+    order_config: order::Config,
+    order_link_id: String,
+    order_manage: Arc<Mutex<OrderInfo>>,
+    // 注文タスクの最終応答時刻
+    heartbeat: Arc<Mutex<Instant>>,
+    finished: CancellationToken,
+    // 板の更新時に全ての側の自己注文の価格及び数量を送信する
+    tx_order: mpsc::Sender<Vec<(f64, f64)>>,
+    // 同じ側で上位の壁に分散する場合の順位、0が最良価格側の壁
+    rank: usize,
+}
+
+// 注文の一時停止の要因
+// いずれかの要因が続く間は注文しない
+#[derive(Debug, Clone, Default)]
+struct Halt {
+    // 急変による停止の再開時刻
+    until: Option<Instant>,
+    // 板またはTickerの更新の途絶
+    is_stale: bool,
+    // 板の整合性の異常、再取得で解消するまで継続する
+    is_invalid_book: bool,
+    // 切断中の購読
+    disconnected: HashSet<String>,
+    // 板の再接続後、Snapshotを受信するまで継続する
+    is_awaiting_snapshot: bool,
+}
+
+impl Halt {
+    fn is_halted(&self, now: Instant) -> bool {
+        self.until.is_some_and(|until| now < until)
+            || self.is_stale
+            || self.is_invalid_book
+            || !self.disconnected.is_empty()
+            || self.is_awaiting_snapshot
+    }
+}
+
+// 両側の注文タスクで共有するデータ
+#[derive(Clone)]
+struct LegShared {
+    is_test: bool,
+    exchange_client: Arc<ToExchange>,
+    logger: Arc<RwLock<Logger>>,
+    cancel_handle: CancellationToken,
+    // 未完了の側の数、全ての側が完了したらRunnerを終了する
+    remaining_legs: Arc<AtomicUsize>,
+    // 両側で追従する場合に共有する建玉の上限
+    max_position: Option<f64>,
+    // 取引所のREST APIで取得した建玉、約定・建玉の更新ごとに取得し直す
+    // why: 建玉の上限の判定に、取引所の現在の建玉を使用する
+    net_position: Arc<RwLock<f64>>,
+    // 板、Ticker及び建玉の最新値、戦略へ渡す
+    board: Arc<board::book::Orderboard>,
+    rx_ticker: watch::Receiver<Ticker>,
+    rx_positions: watch::Receiver<Vec<Position>>,
+    // 約定時に反対売買するヘッジ先
+    hedger: Option<Arc<Hedger>>,
+    // 急変・更新の途絶による一時停止
+    rx_halt: watch::Receiver<Halt>,
+    // 発注前の上限及び当日の確定損益
+    symbol: String,
+    risk_config: risk::Config,
+    risk_ledger: Arc<RwLock<risk::Ledger>>,
+    // 全ての側の注文状態、板に出している注文数の算出に使用する
+    orders: Vec<Arc<Mutex<OrderInfo>>>,
+    // 起動中の損益及び手数料
+    pnl: Arc<RwLock<pnl::Tracker>>,
+}
 
 pub async fn runner(
     controller: Arc<RwLock<Controller>>,
     logger: Arc<RwLock<Logger>>,
-) -> Result<Vec<JoinHandle<()>>, JoinError> {
+    risk_ledger: Arc<RwLock<risk::Ledger>>,
+) -> Result<(Vec<JoinHandle<()>>, RunnerContext), JoinError> {
     // テストの場合注文の処理をスキップする
     let is_test = env::var("IS_TEST").unwrap_or_default() == "true";
 
     // Runner内の処理を並列に実行するためのハンドル
     let mut handles = vec![];
     // Runner内の処理の一つが終了または失敗したら、他の処理を停止するためのハンドル
-    let cancel_handle = tokio_util::sync::CancellationToken::new();
+    let cancel_handle = CancellationToken::new();
 
     // 当関数内のみで使用する変数を生成
     // 当関数はControllerが更新されるごとに再生成される
     // Websocketの送受信用のチャネルは都度接続され、停止される
-    let (target_symbol, exchange_config, board_config, order_config) = {
+    let (target_symbol, exchange_config, order_config, leg_configs, hedge_config, breaker_config, stale_config, risk_config, fee_config) = {
         let r = controller.read().await;
         (
             r.order.symbol.clone(),
             Arc::new(r.exchange.clone()),
-            Arc::new(r.board.clone()),
             r.order.clone(),
+            r.legs(),
+            r.hedge.clone(),
+            r.breaker.clone(),
+            r.stale.clone(),
+            r.risk.clone(),
+            r.fee.clone(),
         )
     };
 
     // 各対応取引所のクライアントを内包する汎化取引所クライアントを生成
-    let exchange_client = ToExchange::create_client(&exchange_config, target_symbol.clone());
+    // 注文タスクと監視タスクで共有する
+    let exchange_client = Arc::new(ToExchange::create_client(&exchange_config, target_symbol.clone()));
+    // 各側で同じ注文IDを使用するため、監視タスクからも注文IDを特定できる
+    // 反対側は接頭辞を共有し、前回起動時の注文の整理対象とする
+    // 同じ口座・銘柄のRunnerは同時に起動できないため、Runner IDは含めず自己注文IDの上限（Bybitは36文字）に収める
+    let set_order_link_id = format!("{}_{}_board4rs", exchange_config.name.as_str(), target_symbol.clone());
 
     // 直列に実行するためのチャネル
     let (tx_ws_orderboard, mut rx_ws_orderboard) = mpsc::channel::<Orderboard>(32);
     let (tx_ws_ticker, mut rx_ws_ticker) = mpsc::channel::<Ticker>(32);
     #[allow(unused_variables, unused_mut)]
     let (tx_ws_position, mut rx_ws_position) = mpsc::channel::<Vec<Position>>(32);
-    let (tx_order, mut rx_order) = mpsc::channel::<f64>(32);
 
     // RestRequest依頼の送受信用のチャネル
     #[allow(unused_variables)]
     let (fetch_rest_ticker, recive_rest_ticker) = mpsc::channel::<()>(32);
-    #[allow(unused_variables)]
     let (fetch_rest_orderboard, recive_rest_orderboard) = mpsc::channel::<()>(32);
     #[allow(unused_variables)]
     let (fetch_rest_position, recive_rest_position) = mpsc::channel::<()>(32);
@@ -65,34 +183,70 @@ pub async fn runner(
     let (tx_rest_orderboard, _) = broadcast::channel::<Orderboard>(32);
     let (tx_rest_position, _) = broadcast::channel::<Vec<Position>>(32);
 
+    // WebSocketの接続状態の通知用のチャネル
+    let (tx_connection, mut rx_connection) = mpsc::channel::<connection::Event>(32);
+
     // 共有更新データ群
     // - スレッド間共有使用データ
-    let order_manage = Arc::new(Mutex::new(order_config.to_order_info()));
+    // - 各側の注文状態及び注文タスクへの送信チャネル
+    // - 上位の壁に分散する場合は、各壁の子注文を独立した側として扱う
+    // 片側のみの場合は従来通り建玉による制限を行わない
+    let max_position = if leg_configs.len() > 1 {
+        leg_configs.iter().map(|(_, c)| c.size).reduce(f64::max)
+    } else {
+        None
+    };
+    let mut legs = vec![];
+    let mut rx_orders = vec![];
+    for (board_config, side_order_config) in leg_configs.into_iter() {
+        let board_config = Arc::new(board_config);
+        for (rank, leg_order_config) in side_order_config.ladder().into_iter().enumerate() {
+            let i = legs.len();
+            let (tx_order, rx_order) = mpsc::channel::<Vec<(f64, f64)>>(32);
+            legs.push(OrderLeg {
+                board_config: board_config.clone(),
+                order_link_id: if i == 0 { set_order_link_id.clone() } else { format!("{}_{}", set_order_link_id, i) },
+                order_manage: Arc::new(Mutex::new(leg_order_config.to_order_info())),
+                order_config: leg_order_config,
+                heartbeat: Arc::new(Mutex::new(Instant::now())),
+                finished: CancellationToken::new(),
+                tx_order,
+                rank,
+            });
+            rx_orders.push(rx_order);
+        }
+    }
 
     // 共有更新データ群
     // - 外部データ: 更新スレッド外で値が必要になり次第取得する
-    let ticker = Arc::new(RwLock::new(Ticker::default()));
+    // - 更新通知: 戦略のon_ticker, on_positionを呼び出す
+    let (tx_ticker, rx_ticker) = watch::channel(Ticker::default());
+    let (tx_positions, rx_positions) = watch::channel::<Vec<Position>>(vec![]);
     let positions = Arc::new(RwLock::new(vec![]));
+    let board = Arc::new(board::book::Orderboard::new());
+    let pnl = Arc::new(RwLock::new(pnl::Tracker::new(target_symbol.clone(), fee_config)));
+    // 注文の一時停止、各検出タスクが要因を更新し、各側の注文タスクが参照する
+    let (tx_halt, rx_halt) = watch::channel(Halt::default());
+    let tx_halt = Arc::new(tx_halt);
 
-    let cloned_ticker = ticker.clone();
+    let cloned_cancel_handle = cancel_handle.clone();
     handles.push(spawn(async move {
         // WebSocketの受信
         loop {
             tokio::select! {
                 Some(t) = rx_ws_ticker.recv() => {
-                    let mut w = cloned_ticker.write().await;
-                    *w = t.clone();
-
                     trace!("ticker: {:?}", t);
+                    tx_ticker.send_replace(t);
                 }
-                _ = pending::<()>() => {
-                    // handle.abort()を待つ
+                _ = cloned_cancel_handle.cancelled() => {
+                    // 停止命令を受けて終了する
+                    break;
                 }
             }
         }
     }));
 
-    let cloned_positions = positions.clone();
+    let (cloned_positions, cloned_cancel_handle) = (positions.clone(), cancel_handle.clone());
     handles.push(spawn(async move {
         // WebSocketの受信
         loop {
@@ -104,19 +258,23 @@ pub async fn runner(
                     }
 
                     info!("position: {:?}", pos);
+                    tx_positions.send_replace(pos);
                 }
-                _ = pending::<()>() => {
-                    // handle.abort()を待つ
+                _ = cloned_cancel_handle.cancelled() => {
+                    // 停止命令を受けて終了する
+                    break;
                 }
             }
         }
     }));
 
-    let ( cloned_order_manage, cloned_board_config, cloned_logger) = {
-        ( order_manage.clone(),  board_config.clone(), logger.clone())
+    let (cloned_board, cloned_legs, cloned_tx_halt, cloned_fetch_rest_orderboard, cloned_logger, cloned_cancel_handle) = {
+        (board.clone(), legs.clone(), tx_halt.clone(), fetch_rest_orderboard.clone(), logger.clone(), cancel_handle.clone())
     };
     handles.push(spawn(async move {
-        let board = Arc::new(board::book::Orderboard::new());
+        let board = cloned_board;
+        // 板の異常を検出して再取得を依頼した時刻
+        let mut resnapshot_at: Option<Instant> = None;
         // WebSocketの送信
         loop {
             tokio::select! {
@@ -128,7 +286,10 @@ pub async fn runner(
                             // 板を差し替える
                             board.replace(BookSide::Bid, books.b);
                             board.replace(BookSide::Ask, books.a);
+                            cloned_tx_halt.send_if_modified(|h| std::mem::take(&mut h.is_awaiting_snapshot));
                         }
+                        // 再接続後のSnapshotより前の差分は破棄した板に適用しない
+                        DataType::UpdateDelta if cloned_tx_halt.borrow().is_awaiting_snapshot => continue,
                         DataType::UpdateDelta => {
                             // size: 0の場合は削除
                             // 同priceは上書き
@@ -136,6 +297,37 @@ pub async fn runner(
                             board.update_delta(BookSide::Ask, books.a);
                         }
                     }
+                    board.update_at();
+
+                    // 板の整合性の確認
+                    // 異常があれば注文を停止して板を再取得し、整合した時点で再開する
+                    // why: 差分を取りこぼした板は交差したまま壁を検出し、不利な価格で注文する
+                    let log = match (board.integrity_error(ORDERBOARD_DEPTH as usize), resnapshot_at) {
+                        (Some(reason), None) => {
+                            cloned_tx_halt.send_modify(|h| h.is_invalid_book = true);
+                            resnapshot_at = Some(Instant::now());
+                            let _ = cloned_fetch_rest_orderboard.try_send(());
+                            Some(("error", format!("[book] pause quoting, resnapshot orderboard: {}", reason)))
+                        }
+                        (Some(reason), Some(at)) if at.elapsed() >= RESNAPSHOT_INTERVAL => {
+                            resnapshot_at = Some(Instant::now());
+                            let _ = cloned_fetch_rest_orderboard.try_send(());
+                            Some(("error", format!("[book] orderboard is still invalid, resnapshot again: {}", reason)))
+                        }
+                        (None, Some(_)) => {
+                            cloned_tx_halt.send_modify(|h| h.is_invalid_book = false);
+                            resnapshot_at = None;
+                            Some(("info", "[book] orderboard is sound, resume quoting".to_string()))
+                        }
+                        _ => None,
+                    };
+                    if let Some((level, message)) = log {
+                        add_log(&cloned_logger, level, message).await;
+                    }
+                    // 異常な板は各側の戦略へ渡さない
+                    if resnapshot_at.is_some() {
+                        continue;
+                    }
 
                     // env_logger traceであれば表示
                     if log_enabled!(log::Level::Info)  {
@@ -146,209 +338,285 @@ pub async fn runner(
                         info!("mid: {}", (best_ask + best_bid)/ 2.0);
                     };
 
-                    // 対象の板を検出する
-                    // - 指定価格内
-                    // - 指定サイズ以上
-                    // - 自己注文価格以外
-                    let start = Instant::now();   
-                    let (target_price, is_there) = {
-                        let prev_order_price = {
-                            let r = cloned_order_manage.lock().await;
-                            r.price
-                        };
-                        board.target_book(&cloned_board_config, prev_order_price)
-                    };
-                    info!("search target price elapsed: {:?}", start.elapsed());
-
-                    if !is_there {
-                        continue;
+                    // 自己注文の価格及び数量を各側の注文タスクへ送信する
+                    // 対象の板の検出は各側の戦略が行う
+                    // 上位の壁に分散する場合、両側で追従する場合は全ての子注文を除外対象とする
+                    let mut own_orders = vec![];
+                    for leg in cloned_legs.iter() {
+                        let r = leg.order_manage.lock().await;
+                        if let Some(price) = r.price {
+                            own_orders.push((price, r.working_qty));
+                        }
                     }
 
-                    trace!("target_price before: {:?}", target_price);
-                    match tx_order.send(target_price).await {
-                        Ok(_) => {
-                            continue;
-                        }
-                        Err(e) => {
-                                let mut w = cloned_logger.write().await;
-                                w.add(Log {
-                                    level: "error".to_string(),
-                                    message: format!("board send error: {:?}", e),
-                                    timestamp: chrono::Local::now().to_string(),
-                                });
-
-                            continue;
+                    for leg in cloned_legs.iter() {
+                        // 注文タスクが処理中であれば、次の板の更新で改めて判定するため送信しない
+                        // 完了した側の注文タスクは終了しているため送信できない
+                        if let Err(mpsc::error::TrySendError::Closed(_)) = leg.tx_order.try_send(own_orders.clone()) {
+                            if leg.finished.is_cancelled() {
+                                continue;
+                            }
+
+                            let mut w = cloned_logger.write().await;
+                            w.add(Log {
+                                level: "error".to_string(),
+                                message: "board send error: order task is closed".to_string(),
+                                timestamp: chrono::Local::now().to_string(),
+                            });
                         }
-                    };
+                    }
+                }
+                _ = cloned_cancel_handle.cancelled() => {
+                    // 停止命令を受けて終了する
+                    break;
                 }
-                _ = pending::<()>() => {
-                    // handle.abort()を待つ
-                },
             }
         }
     }));
 
-    // 設定情報
-    let (cloned_cancel_handle, cloned_exchange, cloned_target_symbol, cloned_order_manage, cloned_positions, cloned_logger, cloned_fetch_rest_position, rx_rest_position) = (
-        cancel_handle.clone(),
-        exchange_config.name.clone(),
-        target_symbol.clone(),
-        order_manage.clone(),
-        positions.clone(),
-        logger.clone(),
-        fetch_rest_position.clone(),
-        tx_rest_position.subscribe(),
-    );
-    handles.push(spawn(async move {
-        let set_order_link_id = format!("{}_{}_board4rs", cloned_exchange.clone().as_str(), cloned_target_symbol.clone());
+    // 取引所側の切断時全キャンセルを設定する
+    // why: アプリの異常終了時にWatchdogも停止するため、最後の注文が取引所に残り続ける
+    if !is_test && order_config.dcp_window_sec > 0 {
+        let log = match exchange_client.arm_dead_man(order_config.dcp_window_sec).await {
+            Ok(_) => Log {
+                level: "info".to_string(),
+                message: format!("[watchdog] disconnect cancel armed, window: {}s", order_config.dcp_window_sec),
+                timestamp: chrono::Local::now().to_string(),
+            },
+            Err(e) => Log {
+                level: "error".to_string(),
+                message: format!("[watchdog] disconnect cancel is not armed: {}", e),
+                timestamp: chrono::Local::now().to_string(),
+            },
+        };
+        let mut w = logger.write().await;
+        w.add(log);
+    }
 
-        loop {
-            tokio::select! {
-                Some(target_price) = rx_order.recv() => {
-                    trace!("target_price after: {:?}",  target_price);
-                    // 条件を満たす対象の情報を受信する
-                    // - is_allowed: interval_sec以上経過しているか
-                    // why: あまりにも頻繁な注文を回避する
-                    let order_id = {
-                        let r = cloned_order_manage.lock().await;
-                        if !r.is_allowed() {
-                            continue;
-                        }
-                        r.order_id.clone()
-                    };
+    // 前回起動時の注文を引き継ぐ、またはキャンセルする
+    // why: 起動時のOrderInfoは空であり、前回の注文を認識できない
+    if !is_test {
+        let orders = legs.iter().map(|leg| (leg.order_link_id.clone(), leg.order_manage.clone())).collect::<Vec<_>>();
+        let log = reconcile::reconcile_orphans(&exchange_client, &orders, &order_config.orphan, &set_order_link_id).await;
+        let mut w = logger.write().await;
+        w.add(log);
+    }
 
-                    // - cancel: order_idがある場合、キャンセルする
-                    if let Some(order_id) = order_id.clone() {
-                        trace!("cancel by order id: {:?}", order_id);
-                        if !is_test {
-                            exchange_client.cancel_order(order_id.clone()).await.unwrap();
-                        }
+    // 注文タスクの最終応答時刻
+    // 注文タスクが一定間隔で更新し、監視タスクが経過時間を確認する
+    if order_config.heartbeat_timeout_sec > 0 {
+        let (cloned_exchange_client, cloned_legs, cloned_logger, cloned_cancel_handle) = (
+            exchange_client.clone(),
+            legs.clone(),
+            logger.clone(),
+            cancel_handle.clone(),
+        );
+        let timeout = Duration::from_secs(order_config.heartbeat_timeout_sec);
+        handles.push(spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            // 同じ無応答期間に対しては一度だけキャンセルする
+            let mut is_fired = vec![false; cloned_legs.len()];
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = cloned_cancel_handle.cancelled() => {
+                        // 停止時の注文キャンセルは注文タスクが行う
+                        break;
                     }
+                }
 
+                for (i, leg) in cloned_legs.iter().enumerate() {
+                    // 完了した側は応答しない
+                    if leg.finished.is_cancelled() {
+                        continue;
+                    }
 
+                    let elapsed = {
+                        let r = leg.heartbeat.lock().await;
+                        r.elapsed()
+                    };
+                    if elapsed < timeout {
+                        is_fired[i] = false;
+                        continue;
+                    }
+                    if is_fired[i] {
+                        continue;
+                    }
+                    is_fired[i] = true;
 
-                    // 建玉を取得し、残りの数量を計算する
-                    // 部分約定があれば、その分を差し引き、再注文する
-                    let ramaining_qty_as_order_qty = {
-                        let prev_order = {
-                            let r = cloned_order_manage.lock().await;
-                            r.clone()
-                        };
-
-                        // 自己注文の価格と同値であれば、注文しない
-                        // Boardでもチェックして、二重チェック
-                        if prev_order.price.unwrap_or_default() == target_price {
-                            info!("order and target_price are same price: {}", target_price);
-                            continue;   
-                        }
+                    // 注文タスクがロックを保持したまま停止している可能性があるため、待機しない
+                    let order_id = match leg.order_manage.try_lock() {
+                        Ok(r) => r.order_id.clone(),
+                        Err(_) => Some(leg.order_link_id.clone()),
+                    };
+                    let order_id = match order_id {
+                        Some(v) => v,
+                        None => continue,
+                    };
 
-                        // Websocket非実装取引所の場合、ポジションは空であるため
-                        // REST APIで取得する
-                        let resubscribed_positions = rx_rest_position.resubscribe();
-                        let temp = position::get_positions(cloned_positions.clone(), cloned_fetch_rest_position.clone(),resubscribed_positions).await;
-
-                        // 先注文があれば、部分約定の可能性がある
-                        // 建玉の確認を行い、指定枚数以上の約定を確認する
-                        // 建玉がなければ、注文数量をそのまま使用する
-                        // 建玉があれば、部分約定の数量を差し引いた数量を使用する
-                        if let Some(order_id) = order_id.clone() {
-                            let has_position = position::aggrigate_position(order_id.clone(), temp);
-                            let remain = prev_order.qty - has_position.qty;
-                            if remain <= 0.0 {
-                                // すべて約定している場合はログを追加
-                                let mut w = cloned_logger.write().await;
-                                w.add(Log {
-                                    level: "success".to_string(),
-                                    message: format!("[completed] close runner by latest order id: {:?}, order size: {}, executed size: {}", order_id, remain, has_position.qty),
-                                    timestamp: chrono::Local::now().to_string(),
-                                });
-
-                                // 終了フラグを立てる
-                                cloned_cancel_handle.cancel();
-                                // 終了フラグはRunner.handlesが管理するspawn処理の.awaitに対して伝播し、全てのspawnが終了する
-                                break;
-                            } 
-                            
-                            remain
-                            
-                        } else {
-                            prev_order.qty
+                    let message = if is_test {
+                        format!("[watchdog][test] order task has not heartbeated for {:?}, cancel order: {}", elapsed, order_id)
+                    } else {
+                        match cloned_exchange_client.cancel_order(order_id.clone()).await {
+                            Ok(_) => format!("[watchdog] order task has not heartbeated for {:?}, cancelled order: {}", elapsed, order_id),
+                            Err(e) => format!("[watchdog] order task has not heartbeated for {:?}, cancel order: {}, error: {}", elapsed, order_id, e),
                         }
                     };
+                    add_log(&cloned_logger, "error", message).await;
+                }
+            }
+        }));
+    }
 
-                    // - add_tick_size: 対象価格に対してtick_sizeを加算する
-                    // why: 取引所の指定する最小価格値を加算または減算し、約定有利な価格を設定する
-                    // 設定型にはticker baseのティックサイズは入っている
-                    let target_price = order_config.add_tick_size(target_price);
-
-                    // - order: 新規注文または再注文を行う
-                    // 約定が指定サイズ以上であれば、再注文前にほか全ての処理を終了する
-                    let order_params = OrderParams {
-                        // 同じ注文IDを使用する
-                        order_id: Some(set_order_link_id.clone()),
-                        side: order_config.side.clone(),
-                        price: target_price,
-                        qty: ramaining_qty_as_order_qty,
-                        is_post_only: order_config.is_post_only,
-                    };
-                    if is_test {
-                        info!("[test] order created, params: {:?}", order_params.clone());
-                        let mut w = cloned_order_manage.lock().await;
-                        w.set_order(order_params.clone().order_id.unwrap().clone());
-
-                        let mut w = cloned_logger.write().await;
-                        w.add(Log {
-                            level: "info".to_string(),
-                            message: format!("[test] order created, params: {:?}", order_params.clone()),
-                            timestamp: chrono::Local::now().to_string(),
-                        });
-                        continue;
+    // 急変の検出
+    // Tickerの最終約定価格から算出し、停止時は再開時刻を各側の注文タスクへ通知する
+    // why: 相場の急変中に壁の前へ並ぶと、壁ごと約定して不利な建玉を抱える
+    if let Some(config) = breaker_config {
+        let (tx_halt, mut cloned_rx_ticker, cloned_logger, cloned_cancel_handle) = (tx_halt.clone(), rx_ticker.clone(), logger.clone(), cancel_handle.clone());
+        handles.push(spawn(async move {
+            let mut breaker = Breaker::new(config);
+            loop {
+                tokio::select! {
+                    Ok(()) = cloned_rx_ticker.changed() => {
+                        let price = {
+                            let t = cloned_rx_ticker.borrow_and_update();
+                            // 最終約定価格がない場合は仲値を使用する
+                            if t.ltp > 0.0 { t.ltp } else { (t.best_ask + t.best_bid) / 2.0 }
+                        };
+
+                        let (level, message) = match breaker.update(Instant::now(), price) {
+                            Some(breaker::Event::Trip { reason, until }) => {
+                                tx_halt.send_modify(|h| h.until = Some(until));
+                                ("error", format!("[breaker] pause quoting for {:?}: {}", until.saturating_duration_since(Instant::now()), reason))
+                            }
+                            Some(breaker::Event::Resume) => {
+                                tx_halt.send_modify(|h| h.until = None);
+                                ("info", format!("[breaker] resume quoting at price: {}", price))
+                            }
+                            None => continue,
+                        };
+
+                        add_log(&cloned_logger, level, message).await;
                     }
+                    _ = cloned_cancel_handle.cancelled() => {
+                        // 停止命令を受けて終了する
+                        break;
+                    }
+                }
+            }
+        }));
+    }
 
-                    match exchange_client.place_order(&order_params.clone()).await {
-                        Ok(latest_order_id) => {
-                            // - set_order: 注文ID及び最終注文時間を更新する
-                            {
-                                let mut w = cloned_order_manage.lock().await;
-                                w.set_order(latest_order_id.clone());
+    // 板及びTickerの更新の途絶の検出
+    // 接続が残ったまま配信が止まると、古い板を基に注文を出し続ける
+    // why: 切断を伴わない停滞はWebsocketの再接続では検出できない
+    if let Some(config) = stale_config {
+        let (tx_halt, cloned_board, mut cloned_rx_ticker, cloned_logger, cloned_cancel_handle) = (tx_halt.clone(), board.clone(), rx_ticker.clone(), logger.clone(), cancel_handle.clone());
+        handles.push(spawn(async move {
+            let mut monitor = stale::Monitor::new(config);
+            let mut ticker_at = Instant::now();
+            let mut interval = tokio::time::interval(Duration::from_millis(100));
+            loop {
+                tokio::select! {
+                    Ok(()) = cloned_rx_ticker.changed() => {
+                        cloned_rx_ticker.borrow_and_update();
+                        ticker_at = Instant::now();
+                    }
+                    _ = interval.tick() => {
+                        let book_elapsed = (chrono::Utc::now() - cloned_board.last_update_at()).to_std().unwrap_or_default();
+                        let (level, message) = match monitor.check(book_elapsed, ticker_at.elapsed()) {
+                            Some(stale::Event::Stale { reason }) => {
+                                tx_halt.send_modify(|h| h.is_stale = true);
+                                ("error", format!("[stale] pause quoting: {}", reason))
                             }
+                            Some(stale::Event::Resume) => {
+                                tx_halt.send_modify(|h| h.is_stale = false);
+                                ("info", "[stale] resume quoting: market data is updated".to_string())
+                            }
+                            None => continue,
+                        };
 
-                            let mut w = cloned_logger.write().await;
-                            w.add(Log {
-                                level: "info".to_string(),
-                                message: format!("order created, params: {:?}", order_params),
-                                timestamp: chrono::Local::now().to_string(),
-                            });
-                        }
-                        Err(e) => {
-                                {
-                                    let mut w = cloned_order_manage.lock().await;
-                                    w.set_error_order();
-                                }
+                        add_log(&cloned_logger, level, message).await;
+                    }
+                    _ = cloned_cancel_handle.cancelled() => {
+                        // 停止命令を受けて終了する
+                        break;
+                    }
+                }
+            }
+        }));
+    }
 
-                                let mut w = cloned_logger.write().await;
-                                w.add(Log {
-                                    level: "error".to_string(),
-                                    message: format!("order error: {:?}", e),
-                                    timestamp: chrono::Local::now().to_string(),
-                                });
-                            continue;
+    // WebSocketの接続状態の監視
+    // 切断中は注文を停止し、板の再接続時は板を破棄してSnapshotを受信するまで停止を継続する
+    // why: 切断中に取りこぼした差分は再接続後に届かず、古い板に差分を適用すると壊れた板になる
+    let (cloned_tx_halt, cloned_board, cloned_fetch_rest_orderboard, cloned_logger, cloned_cancel_handle) = {
+        (tx_halt.clone(), board.clone(), fetch_rest_orderboard.clone(), logger.clone(), cancel_handle.clone())
+    };
+    handles.push(spawn(async move {
+        loop {
+            tokio::select! {
+                Some(event) = rx_connection.recv() => {
+                    let (level, message) = match event {
+                        connection::Event::Disconnected { stream, reason } => {
+                            let message = format!("[ws] pause quoting, {} is disconnected: {}", stream, reason);
+                            cloned_tx_halt.send_modify(|h| { h.disconnected.insert(stream); });
+                            ("error", message)
+                        }
+                        connection::Event::Reconnected { stream } => {
+                            if stream == connection::ORDERBOARD {
+                                cloned_board.replace(BookSide::Bid, vec![]);
+                                cloned_board.replace(BookSide::Ask, vec![]);
+                                cloned_tx_halt.send_modify(|h| h.is_awaiting_snapshot = true);
+                                // 再購読のSnapshotが届かない場合に備えて再取得も依頼する
+                                let _ = cloned_fetch_rest_orderboard.try_send(());
+                            }
+                            let message = format!("[ws] {} is reconnected and resubscribed", stream);
+                            cloned_tx_halt.send_modify(|h| { h.disconnected.remove(&stream); });
+                            ("info", message)
                         }
                     };
+                    add_log(&cloned_logger, level, message).await;
                 }
-                _ = pending::<()>() => {
-                    // handle.abort()を待つ
+                _ = cloned_cancel_handle.cancelled() => {
+                    // 停止命令を受けて終了する
+                    break;
                 }
             }
         }
     }));
 
+    // 設定情報
+    let shared = LegShared {
+        is_test,
+        exchange_client: exchange_client.clone(),
+        logger: logger.clone(),
+        cancel_handle: cancel_handle.clone(),
+        remaining_legs: Arc::new(AtomicUsize::new(legs.len())),
+        max_position,
+        net_position: Arc::new(RwLock::new(0.0)),
+        board,
+        rx_ticker: rx_ticker.clone(),
+        rx_positions,
+        hedger: hedge_config.map(|config| Arc::new(Hedger::new(config))),
+        rx_halt,
+        symbol: target_symbol.clone(),
+        risk_config,
+        risk_ledger,
+        orders: legs.iter().map(|leg| leg.order_manage.clone()).collect(),
+        pnl: pnl.clone(),
+    };
+    for (leg, rx_order) in legs.iter().zip(rx_orders) {
+        handles.push(spawn_order_leg(leg.clone(), rx_order, shared.clone()));
+    }
+
     let handle_orderboard = exchange_config
         .orderboard(
             target_symbol.clone(),
             tx_ws_orderboard,
             recive_rest_orderboard,
             tx_rest_orderboard,
+            tx_connection.clone(),
         )
         .await
         .unwrap();
@@ -360,6 +628,7 @@ pub async fn runner(
             tx_ws_ticker,
             recive_rest_ticker,
             tx_rest_ticker,
+            tx_connection.clone(),
         )
         .await
         .unwrap();
@@ -371,11 +640,740 @@ pub async fn runner(
             tx_ws_position,
             recive_rest_position,
             tx_rest_position,
+            tx_connection.clone(),
         )
         .await
         .unwrap();
     handles.push(handle_position);
 
-    Ok(handles)
+    Ok((
+        handles,
+        RunnerContext {
+            cancel_handle,
+            finished: legs.iter().map(|leg| leg.finished.clone()).collect(),
+            orders: legs.iter().map(|leg| leg.order_manage.clone()).collect(),
+            positions,
+            pnl,
+            rx_ticker,
+        },
+    ))
+}
+
+// 片側の注文タスク
+// 板・Ticker・建玉の更新を戦略へ渡し、戦略の注文意図を実行する
+fn spawn_order_leg(leg: OrderLeg, mut rx_order: mpsc::Receiver<Vec<(f64, f64)>>, shared: LegShared) -> JoinHandle<()> {
+    let mut strategy = strategy::build(leg.board_config.clone(), leg.order_config.clone(), leg.order_link_id.clone(), leg.rank, shared.max_position);
+    let (mut rx_ticker, mut rx_positions, mut rx_halt) = (shared.rx_ticker.clone(), shared.rx_positions.clone(), shared.rx_halt.clone());
+
+    spawn(async move {
+        // 完了・停止・異常終了のいずれでも終了を通知する
+        let _finished = leg.finished.clone().drop_guard();
+        refresh_net_position(&shared).await;
+        let mut heartbeat_interval = tokio::time::interval(Duration::from_secs(1));
+        let mut execution_interval = tokio::time::interval(EXECUTION_INTERVAL);
+
+        loop {
+            let intents = tokio::select! {
+                _ = heartbeat_interval.tick() => {
+                    // 受信待ちの間も応答していることを監視タスクへ通知する
+                    let mut w = leg.heartbeat.lock().await;
+                    *w = Instant::now();
+                    continue;
+                }
+                _ = execution_interval.tick() => {
+                    // 板に出している注文の約定を自己注文IDで照会する
+                    // why: 建玉の情報には自己注文IDが含まれず、建玉から側ごとの約定を識別できない
+                    let (is_working, is_pending_hedge) = {
+                        let r = leg.order_manage.lock().await;
+                        (r.price.is_some(), r.pending_hedge.is_some())
+                    };
+                    if shared.is_test {
+                        continue;
+                    }
+                    if is_working && sync_execution(&leg, &shared).await.is_break() {
+                        break;
+                    }
+                    // 読み戻せなかったヘッジ注文は次の約定を待たずに照会する
+                    if is_pending_hedge && hedge(&leg, &shared).await.is_break() {
+                        break;
+                    }
+                    continue;
+                }
+                Some(own_orders) = rx_order.recv() => {
+                    // 急変・更新の途絶による停止中は注文しない
+                    if rx_halt.borrow().is_halted(Instant::now()) {
+                        continue;
+                    }
+
+                    // 板の更新を受信する
+                    // 発注頻度の制限は戦略の注文意図を実行する際に行う
+                    let order = leg.order_manage.lock().await.clone();
+
+                    let net_position = *shared.net_position.read().await;
+                    let ticker = rx_ticker.borrow().clone();
+
+                    strategy.on_book(&shared.board, &own_orders, &State { order: &order, ticker: &ticker, net_position })
+                }
+                Ok(()) = rx_halt.changed() => {
+                    // 急変・更新の途絶を検出した場合、板に出している注文を取り下げる
+                    if !rx_halt.borrow_and_update().is_halted(Instant::now()) {
+                        continue;
+                    }
+
+                    let order_id = {
+                        let r = leg.order_manage.lock().await;
+                        r.order_id.clone().filter(|_| r.price.is_some())
+                    };
+                    if let Some(order_id) = order_id {
+                        if cancel(&leg, &shared, order_id).await.is_break() {
+                            break;
+                        }
+                        let mut w = leg.order_manage.lock().await;
+                        w.clear_working();
+                    }
+                    continue;
+                }
+                Ok(()) = rx_ticker.changed() => {
+                    let ticker = rx_ticker.borrow_and_update().clone();
+                    let order = leg.order_manage.lock().await.clone();
+                    let net_position = *shared.net_position.read().await;
+
+                    strategy.on_ticker(&State { order: &order, ticker: &ticker, net_position })
+                }
+                Ok(()) = rx_positions.changed() => {
+                    // 建玉の更新を契機に、取引所の現在の建玉を取得し直す
+                    rx_positions.borrow_and_update();
+                    let net_position = refresh_net_position(&shared).await;
+                    let order = leg.order_manage.lock().await.clone();
+                    let ticker = rx_ticker.borrow().clone();
+
+                    strategy.on_position(&State { order: &order, ticker: &ticker, net_position })
+                }
+                _ = shared.cancel_handle.cancelled() => {
+                    // 停止命令を受けた場合、注文をキャンセルしてから終了する
+                    // why: タスクの中断のみでは注文が取引所に残り続ける
+                    let order_id = {
+                        let r = leg.order_manage.lock().await;
+                        r.order_id.clone()
+                    };
+
+                    let message = match order_id {
+                        Some(order_id) if shared.is_test => format!("[stopped][test] cancel order: {}", order_id),
+                        Some(order_id) => {
+                            match tokio::time::timeout(STOP_CANCEL_TIMEOUT, shared.exchange_client.cancel_order(order_id.clone())).await {
+                                Ok(Ok(_)) => {
+                                    let mut w = leg.order_manage.lock().await;
+                                    w.order_id = None;
+                                    format!("[stopped] cancelled order: {}", order_id)
+                                }
+                                Ok(Err(e)) => format!("[stopped] cancel order: {}, error: {}", order_id, e),
+                                Err(_) => format!("[stopped] cancel order: {}, timeout: {:?}", order_id, STOP_CANCEL_TIMEOUT),
+                            }
+                        }
+                        None => "[stopped] no working order".to_string(),
+                    };
+
+                    add_log(&shared.logger, "info", message).await;
+                    break;
+                }
+            };
+
+            if execute(intents, &leg, &shared, strategy.as_mut()).await.is_break() {
+                break;
+            }
+        }
+    })
 }
 
+// 戦略の注文意図を先頭から実行する
+// 注文タスクを終了する場合はBreakを返す
+async fn execute(intents: Vec<Intent>, leg: &OrderLeg, shared: &LegShared, strategy: &mut dyn Strategy) -> ControlFlow<()> {
+    // - throttle: 発注を伴う場合のみ発注枠を確保する
+    // why: あまりにも頻繁な注文を回避する
+    // 確保できない場合はキャンセルも行わず板に出している注文を維持し、ログのみ出力する
+    let is_placing = intents.iter().any(|intent| matches!(intent, Intent::Place { .. } | Intent::Amend { .. }));
+    let intents = if is_placing && !leg.order_manage.lock().await.throttle.try_acquire(Instant::now()) {
+        trace!("order throttled: {}", leg.order_link_id);
+        intents.into_iter().filter(|intent| matches!(intent, Intent::Log { .. })).collect()
+    } else {
+        intents
+    };
+
+    let mut intents = VecDeque::from(intents);
+    while let Some(intent) = intents.pop_front() {
+        match intent {
+            Intent::Log { level, message } => add_log(&shared.logger, &level, message).await,
+            Intent::Cancel { order_id } => {
+                if cancel(leg, shared, order_id).await.is_break() {
+                    return ControlFlow::Break(());
+                }
+            }
+            Intent::Place { mut params, filled_qty } => {
+                // 戦略の判定後、キャンセル時の照会で増えた約定は注文数量から差し引く
+                let executed_qty = leg.order_manage.lock().await.executed_qty;
+                if executed_qty > filled_qty {
+                    params.qty -= executed_qty - filled_qty;
+                    if params.qty <= 0.0 {
+                        continue;
+                    }
+                }
+                let filled_qty = executed_qty.max(filled_qty);
+                let is_immediate = params.order_type == OrderType::Market || matches!(params.time_in_force, TimeInForce::Ioc | TimeInForce::Fok);
+                let is_placed = match place(leg, shared, params, filled_qty).await {
+                    ControlFlow::Break(()) => return ControlFlow::Break(()),
+                    ControlFlow::Continue(v) => v,
+                };
+                strategy.on_order(&leg.order_manage.lock().await.clone());
+
+                // 即時執行の注文は約定結果を照合する
+                // 設定自体が即時執行であれば1回の送信で完了とし、IOCへの切り替え後は約定分をヘッジして追従を続ける
+                if !is_immediate || !is_placed {
+                    continue;
+                }
+                if let Some((filled_qty, average_price)) = settle(leg, shared).await {
+                    if leg.order_config.is_immediate() {
+                        intents.push_front(Intent::Complete { order_id: leg.order_link_id.clone(), filled_qty, average_price });
+                    } else if hedge(leg, shared).await.is_break() {
+                        return ControlFlow::Break(());
+                    }
+                }
+            }
+            Intent::Amend { order_id, price, qty } => {
+                // 取引所クライアントが注文変更に非対応のため、キャンセル後に再注文する
+                if cancel(leg, shared, order_id).await.is_break() {
+                    return ControlFlow::Break(());
+                }
+                let filled_qty = leg.order_manage.lock().await.executed_qty;
+                let params = OrderParams {
+                    order_id: Some(leg.order_link_id.clone()),
+                    side: leg.order_config.side.clone(),
+                    price,
+                    qty,
+                    order_type: leg.order_config.order_type.clone(),
+                    time_in_force: leg.order_config.time_in_force(),
+                    reduce_only: leg.order_config.reduce_only,
+                };
+                if place(leg, shared, params, filled_qty).await.is_break() {
+                    return ControlFlow::Break(());
+                }
+                strategy.on_order(&leg.order_manage.lock().await.clone());
+            }
+            Intent::Complete { order_id, filled_qty, average_price } => {
+                if hedge(leg, shared).await.is_break() {
+                    return ControlFlow::Break(());
+                }
+                // すべて約定している場合はログを追加
+                add_log(&shared.logger, "success", format!("[completed] close runner by latest order id: {:?}, order size: {}, executed size: {}", order_id, leg.order_config.size, filled_qty)).await;
+
+                // 決済計画があれば、建玉が解消するまで決済注文を追跡する
+                if let Some(plan) = leg.order_config.exit.clone() {
+                    run_exit(leg, shared, &plan, filled_qty, average_price).await;
+                }
+
+                // 起動中の損益を記録し、パラメータごとの比較に使用する
+                let summary = shared.pnl.read().await.summary(shared.rx_ticker.borrow().ltp);
+                add_log(&shared.logger, "success", format!("[pnl] {}", serde_json::to_string(&summary).unwrap_or_default())).await;
+
+                // 全ての側が完了していれば終了フラグを立てる
+                if shared.remaining_legs.fetch_sub(1, Ordering::SeqCst) == 1 {
+                    shared.cancel_handle.cancel();
+                }
+                // 終了フラグはRunner.handlesが管理するspawn処理の.awaitに対して伝播し、全てのspawnが終了する
+                return ControlFlow::Break(());
+            }
+            Intent::Stop { reason } => {
+                add_log(&shared.logger, "error", format!("[stopped] close runner by {}", reason)).await;
+                // 注文のキャンセルは停止命令の受信側で行う
+                shared.cancel_handle.cancel();
+                return ControlFlow::Continue(());
+            }
+        }
+    }
+
+    ControlFlow::Continue(())
+}
+
+// 注文をキャンセルする
+// 約定済み・キャンセル済みの注文は取引所に存在しないため、Fatal以外は継続する
+async fn cancel(leg: &OrderLeg, shared: &LegShared, order_id: String) -> ControlFlow<()> {
+    trace!("cancel by order id: {:?}", order_id);
+    if shared.is_test {
+        return ControlFlow::Continue(());
+    }
+
+    if let Err(e) = shared.exchange_client.cancel_order(order_id).await {
+        add_log(&shared.logger, "error", format!("cancel error: {}", e)).await;
+
+        if e.is_fatal() {
+            add_log(&shared.logger, "error", format!("[stopped] close runner by fatal cancel error: {}", e.message)).await;
+            shared.cancel_handle.cancel();
+            return ControlFlow::Break(());
+        }
+    }
+
+    trace!("cancelled leg: {}", leg.order_link_id);
+    // 取り下げまでの約定を取りこぼさないよう、再注文で照会先が切り替わる前に照会する
+    sync_execution(leg, shared).await
+}
+
+// 新規注文または再注文を行い、注文ID及び注文数量を更新する
+// 発注した場合はtrueを返す
+async fn place(leg: &OrderLeg, shared: &LegShared, order_params: OrderParams, filled_qty: f64) -> ControlFlow<(), bool> {
+    // 発注前に上限を確認し、抵触する場合は発注しない
+    // 次の周期で改めて判定する
+    match check_risk(leg, shared, &order_params).await {
+        Ok(None) => (),
+        Ok(Some(event)) => {
+            add_log(&shared.logger, "error", format!("[risk] {}", serde_json::to_string(&event).unwrap_or_default())).await;
+            return ControlFlow::Continue(false);
+        }
+        Err(e) => {
+            add_log(&shared.logger, "error", format!("[risk] position error: {}", e)).await;
+            return ControlFlow::Continue(false);
+        }
+    }
+
+    // - price_guard: Ticker及び板の最良価格から離れすぎた価格、反対側に届く価格は発注しない
+    // why: 価格範囲の設定ミスや壊れた板から、相場とかけ離れた価格で約定するのを防ぐ
+    let ticker = shared.rx_ticker.borrow().clone();
+    let (best_ask, best_bid) = shared.board.best_prices();
+    let quotes = [
+        ("ticker", Quote { best_ask: ticker.best_ask, best_bid: ticker.best_bid, ltp: ticker.ltp }),
+        ("board", Quote { best_ask, best_bid, ltp: 0.0 }),
+    ];
+    if let Some(reason) = leg.order_config.price_guard.check(&order_params.side, order_params.price, &order_params.order_type, &order_params.time_in_force, &quotes) {
+        add_log(&shared.logger, "error", format!("[guard] rejected order: {}", reason)).await;
+        return ControlFlow::Continue(false);
+    }
+
+    if shared.is_test {
+        info!("[test] order created, params: {:?}", order_params.clone());
+        let suppressed = {
+            let mut w = leg.order_manage.lock().await;
+            w.set_order(order_params.clone().order_id.unwrap_or_default(), order_params.price);
+            w.set_progress(order_params.qty, filled_qty);
+            w.throttle.suppressed
+        };
+
+        add_log(&shared.logger, "info", format!("[test] order created, params: {:?}, throttled: {}", order_params, suppressed)).await;
+        return ControlFlow::Continue(true);
+    }
+
+    match shared.exchange_client.place_order(&order_params).await {
+        Ok(latest_order_id) => {
+            // - set_order: 注文ID及び初回注文時間を更新する
+            // 発注頻度の制限で見送った回数を合わせて出力する
+            let suppressed = {
+                let mut w = leg.order_manage.lock().await;
+                w.set_order(latest_order_id.clone(), order_params.price);
+                w.set_progress(order_params.qty, filled_qty);
+                w.throttle.suppressed
+            };
+
+            add_log(&shared.logger, "info", format!("order created, params: {:?}, throttled: {}", order_params, suppressed)).await;
+            if leg.order_config.clip_size > 0.0 {
+                add_log(&shared.logger, "info", format!("[clip] progress: {}/{}, clip: {}", filled_qty, leg.order_config.size, order_params.qty)).await;
+            }
+            ControlFlow::Continue(true)
+        }
+        Err(e) => {
+            add_log(&shared.logger, "error", format!("order error: {}", e)).await;
+
+            // 再試行しても解消しないエラーはRunnerを停止する
+            // 一時的なエラーはクライアント内で再試行済みのため、次の周期で再注文する
+            if e.is_fatal() {
+                add_log(&shared.logger, "error", format!("[stopped] close runner by fatal order error: {}", e.message)).await;
+                shared.cancel_handle.cancel();
+                return ControlFlow::Break(());
+            }
+            ControlFlow::Continue(false)
+        }
+    }
+}
+
+// 即時執行の注文の約定結果を取得し、損益の集計に記録する
+// 取引所での処理の終了を待ち、板に残らないため注文価格を取り下げる
+// why: 板に並ばない注文をキャンセル・再注文の対象にすると、存在しない注文のキャンセルが失敗し続ける
+// 側の累計約定数量及び平均約定価格を返す
+async fn settle(leg: &OrderLeg, shared: &LegShared) -> Option<(f64, f64)> {
+    let (order_id, working_qty, price) = {
+        let r = leg.order_manage.lock().await;
+        (r.order_id.clone()?, r.working_qty, r.price.unwrap_or_default())
+    };
+
+    let execution = if shared.is_test {
+        // 試験時は注文数量を注文価格で約定したとみなす
+        Some(Execution { order_id: order_id.clone(), is_closed: true, cum_exec_qty: working_qty, average_price: price })
+    } else {
+        let mut execution = None;
+        for _ in 0..SETTLE_MAX_RETRY {
+            match shared.exchange_client.execution(order_id.clone()).await {
+                Ok(Some(v)) if v.is_closed => {
+                    execution = Some(v);
+                    break;
+                }
+                Ok(v) => execution = v,
+                Err(e) => add_log(&shared.logger, "error", format!("[settle] execution error: {}", e)).await,
+            }
+            tokio::time::sleep(SETTLE_INTERVAL).await;
+        }
+        execution
+    };
+
+    {
+        let mut w = leg.order_manage.lock().await;
+        w.clear_working();
+    }
+
+    let execution = match execution {
+        Some(v) => v,
+        None => {
+            add_log(&shared.logger, "error", format!("[settle] execution not found: {}", order_id)).await;
+            return None;
+        }
+    };
+    let (executed_qty, average_price) = {
+        let mut w = leg.order_manage.lock().await;
+        w.apply_execution(execution.cum_exec_qty, execution.average_price);
+        (w.executed_qty, w.average_price())
+    };
+    record_fills(leg, shared).await;
+    refresh_net_position(shared).await;
+    add_log(&shared.logger, "info", format!("[settle] {} executed: {} @ {}, closed: {}", order_id, execution.cum_exec_qty, execution.average_price, execution.is_closed)).await;
+
+    Some((executed_qty, average_price))
+}
+
+// 板に出している注文の約定結果を取得し、側の累計約定数量に加える
+// 約定が増えた場合は損益の集計に記録し、次の発注判定を待たずにヘッジする
+async fn sync_execution(leg: &OrderLeg, shared: &LegShared) -> ControlFlow<()> {
+    let order_id = match leg.order_manage.lock().await.order_id.clone() {
+        Some(v) if !shared.is_test => v,
+        _ => return ControlFlow::Continue(()),
+    };
+
+    let execution = match shared.exchange_client.execution(order_id).await {
+        Ok(Some(v)) => v,
+        Ok(None) => return ControlFlow::Continue(()),
+        Err(e) => {
+            add_log(&shared.logger, "error", format!("execution error: {}", e)).await;
+            return ControlFlow::Continue(());
+        }
+    };
+    let delta = {
+        let mut w = leg.order_manage.lock().await;
+        w.apply_execution(execution.cum_exec_qty, execution.average_price)
+    };
+    if delta <= 0.0 {
+        return ControlFlow::Continue(());
+    }
+
+    record_fills(leg, shared).await;
+    refresh_net_position(shared).await;
+    hedge(leg, shared).await
+}
+
+// 取引所の建玉を取得し、戦略へ渡す建玉を更新する
+// 取得できない場合は前回の値を使用する
+async fn refresh_net_position(shared: &LegShared) -> f64 {
+    match fetch_net_position(shared).await {
+        Ok(v) => {
+            let mut w = shared.net_position.write().await;
+            *w = v;
+            v
+        }
+        Err(e) => {
+            add_log(&shared.logger, "error", format!("position error: {}", e)).await;
+            *shared.net_position.read().await
+        }
+    }
+}
+
+// 買い建玉を正とした取引所の建玉
+// 試験時は発注しないため、損益の集計上の建玉を使用する
+// why: WebSocketの建玉は自己注文IDを含まず、購読の途絶中は更新されない
+async fn fetch_net_position(shared: &LegShared) -> Result<f64, ExchangeError> {
+    if shared.is_test {
+        return Ok(shared.pnl.read().await.summary(0.0).net_position);
+    }
+    Ok(position::net_position(&shared.exchange_client.positions().await?))
+}
+
+// 前回から増えた側の約定を損益の集計に記録する
+// 板に並ぶ注文はメイカー、即時執行の注文及びIOCへの切り替え後はテイカーとみなす
+async fn record_fills(leg: &OrderLeg, shared: &LegShared) {
+    let (executed_qty, average_price, is_escalated) = {
+        let r = leg.order_manage.lock().await;
+        (r.executed_qty, r.average_price(), r.is_escalated(leg.order_config.escalate_after_sec))
+    };
+    if executed_qty <= 0.0 {
+        return;
+    }
+    let is_maker = leg.order_config.order_type == OrderType::Limit
+        && !matches!(leg.order_config.time_in_force(), TimeInForce::Ioc | TimeInForce::Fok)
+        && !is_escalated;
+
+    {
+        let mut w = shared.pnl.write().await;
+        w.record_cumulative(&leg.order_link_id, leg.order_config.side.clone(), executed_qty, average_price, is_maker);
+    }
+    book_realized(shared).await;
+}
+
+// 前回の計上以降の確定損益を当日の損益に加える
+// why: 当日の損失の上限を、決済計画の有無によらず全ての約定から判定する
+async fn book_realized(shared: &LegShared) {
+    let delta = shared.pnl.write().await.take_realized();
+    if delta != 0.0 {
+        let mut w = shared.risk_ledger.write().await;
+        w.record(&shared.symbol, delta, chrono::Utc::now());
+    }
+}
+
+// 前回のヘッジ以降に約定した数量を、ヘッジ先で反対売買する
+// 約定価格は側の平均約定価格とする
+async fn hedge(leg: &OrderLeg, shared: &LegShared) -> ControlFlow<()> {
+    let hedger = match shared.hedger.as_ref() {
+        Some(v) => v,
+        None => return ControlFlow::Continue(()),
+    };
+
+    let prefix = if shared.is_test { "[test][hedge]" } else { "[hedge]" };
+    // 前回のヘッジ注文の約定結果を読み戻せていない場合は、照会して反映するまで次のヘッジ注文を出さない
+    // why: 読み戻せなかった数量を再度ヘッジすると、ヘッジ先の建玉が過大になる
+    let pending = leg.order_manage.lock().await.pending_hedge.clone();
+    if let Some(order_id) = pending {
+        let execution = match hedger.execution(order_id.clone()).await {
+            Ok(Some(v)) if !v.is_closed => return ControlFlow::Continue(()),
+            Ok(v) => v,
+            Err(e) => return hedge_error(shared, prefix, e).await,
+        };
+        // 注文が存在しない場合は発注されなかったとみなす
+        let qty = execution.map(|v| v.cum_exec_qty).unwrap_or(0.0);
+        {
+            let mut w = leg.order_manage.lock().await;
+            w.hedged_qty += qty;
+            w.pending_hedge = None;
+        }
+        add_log(&shared.logger, "info", format!("{} resolved {}: {} {}", prefix, order_id, qty, hedger.config().symbol)).await;
+    }
+
+    let (filled_qty, entry_price, hedged_qty) = {
+        let r = leg.order_manage.lock().await;
+        (r.executed_qty, r.average_price(), r.hedged_qty)
+    };
+    let qty = hedger.config().hedge_qty(filled_qty, hedged_qty);
+    if qty <= 0.0 {
+        return ControlFlow::Continue(());
+    }
+
+    let order_id = hedger.order_id();
+    let params = match hedger.params(order_id.clone(), &leg.order_config.side, qty).await {
+        Ok(v) => v,
+        Err(e) => return hedge_error(shared, prefix, e).await,
+    };
+
+    // ヘッジ注文も発注前に上限を確認し、抵触する場合は次の約定で改めて判定する
+    // why: 主側の上限のみではヘッジ先の建玉及び注文の想定元本が制限されない
+    let event = match check_hedge_risk(shared, hedger, &params).await {
+        Ok(v) => v,
+        Err(e) => return hedge_error(shared, prefix, e).await,
+    };
+    if let Some(event) = event {
+        add_log(&shared.logger, "error", format!("[risk] {}", serde_json::to_string(&event).unwrap_or_default())).await;
+        return ControlFlow::Continue(());
+    }
+
+    // 読み戻しに失敗した場合は次回に照会する
+    leg.order_manage.lock().await.pending_hedge = Some(order_id);
+    match hedger.hedge(&params, &leg.order_config.side, entry_price, shared.is_test).await {
+        Ok(fill) => {
+            {
+                let mut w = leg.order_manage.lock().await;
+                w.hedged_qty += fill.qty;
+                w.pending_hedge = None;
+            }
+
+            add_log(&shared.logger, "info", format!("{} {:?} {}/{} {}, entry price: {}, average price: {}, expected price: {}, slippage: {:.2}bps", prefix, fill.side, fill.qty, qty, hedger.config().symbol, entry_price, fill.price, fill.expected_price, fill.slippage_bps)).await;
+            ControlFlow::Continue(())
+        }
+        Err(e) => hedge_error(shared, prefix, e).await,
+    }
+}
+
+async fn hedge_error(shared: &LegShared, prefix: &str, e: ExchangeError) -> ControlFlow<()> {
+    add_log(&shared.logger, "error", format!("{} order error: {}", prefix, e)).await;
+
+    // ヘッジできない状態で約定を重ねると露出が拡大するため停止する
+    if e.is_fatal() {
+        add_log(&shared.logger, "error", format!("[stopped] close runner by fatal hedge error: {}", e.message)).await;
+        shared.cancel_handle.cancel();
+        return ControlFlow::Break(());
+    }
+    ControlFlow::Continue(())
+}
+
+// ヘッジ先の銘柄・建玉及び当日の確定損益から上限を確認する
+// 試験時はヘッジ先の建玉を取得しない
+async fn check_hedge_risk(shared: &LegShared, hedger: &Hedger, order_params: &OrderParams) -> Result<Option<risk::RiskEvent>, ExchangeError> {
+    let net_position = if shared.is_test { 0.0 } else { hedger.net_position().await? };
+    let exposure = risk::Exposure {
+        net_position,
+        open_orders: 0,
+        ltp: order_params.price,
+    };
+
+    let ledger = shared.risk_ledger.read().await;
+    Ok(shared.risk_config.check(&hedger.config().symbol, order_params, &exposure, &ledger))
+}
+
+// 発注時点の取引所の建玉・注文数・当日の確定損益から上限を確認する
+// why: WebSocketの建玉は購読の途絶中は更新されず、上限の判定には取引所の現在の建玉を使用する
+// 試験時は発注しないため、損益の集計上の建玉を使用する
+async fn check_risk(leg: &OrderLeg, shared: &LegShared, order_params: &OrderParams) -> Result<Option<risk::RiskEvent>, ExchangeError> {
+    // 自身の注文はキャンセル済みのため数えない
+    let mut open_orders = 0;
+    for order in shared.orders.iter().filter(|order| !Arc::ptr_eq(order, &leg.order_manage)) {
+        if order.lock().await.price.is_some() {
+            open_orders += 1;
+        }
+    }
+    let ltp = shared.rx_ticker.borrow().ltp;
+    let exposure = if shared.is_test {
+        risk::Exposure { net_position: shared.pnl.read().await.summary(0.0).net_position, open_orders, ltp }
+    } else {
+        risk::Exposure::from_positions(&shared.exchange_client.positions().await?, open_orders, ltp)
+    };
+
+    let ledger = shared.risk_ledger.read().await;
+    Ok(shared.risk_config.check(&shared.symbol, order_params, &exposure, &ledger))
+}
+
+async fn add_log(logger: &Arc<RwLock<Logger>>, level: &str, message: String) {
+    let mut w = logger.write().await;
+    w.add(Log {
+        level: level.to_string(),
+        message,
+        timestamp: chrono::Local::now().to_string(),
+    });
+}
+
+// 建玉完成後の決済
+// 平均約定価格から利確の指値と任意で損切りの逆指値を出し、建玉が解消するまで追跡する
+// why: 建玉の完成で終了すると、決済が手動になり往復の取引を1回の起動で完結できない
+// 確定損益は損益の集計を通して当日の損益に計上する
+async fn run_exit(leg: &OrderLeg, shared: &LegShared, plan: &ExitPlan, entry_qty: f64, average_price: f64) {
+    let exit_link_id = format!("{}_exit", leg.order_link_id);
+    let stop_link_id = format!("{}_sl", leg.order_link_id);
+    let (take_profit, stop_loss) = plan.prices(&leg.order_config.side, average_price, leg.order_config.tick_size);
+
+    let order_params = OrderParams {
+        order_id: Some(exit_link_id.clone()),
+        side: leg.order_config.exit_side(),
+        price: take_profit,
+        qty: entry_qty,
+        order_type: OrderType::Limit,
+        time_in_force: TimeInForce::Gtc,
+        reduce_only: true,
+    };
+
+    if shared.is_test {
+        add_log(&shared.logger, "info", format!("[test][exit] take profit: {:?}, stop loss: {:?}, average price: {}", order_params, stop_loss, average_price)).await;
+        return;
+    }
+
+    if let Err(e) = shared.exchange_client.place_order(&order_params).await {
+        add_log(&shared.logger, "error", format!("[exit] take profit order error: {}", e)).await;
+        return;
+    }
+    if let Some(stop_loss) = stop_loss {
+        if let Err(e) = shared.exchange_client.stop_loss(leg.order_config.exit_side(), entry_qty, stop_loss, stop_link_id.clone()).await {
+            add_log(&shared.logger, "error", format!("[exit] stop loss error: {}", e)).await;
+        }
+    }
+    add_log(&shared.logger, "info", format!("[exit] take profit: {}, stop loss: {:?}, average price: {}, qty: {}", take_profit, stop_loss, average_price, entry_qty)).await;
+
+    let mut heartbeat_interval = tokio::time::interval(Duration::from_secs(1));
+    let mut check_interval = tokio::time::interval(Duration::from_secs(leg.order_config.interval_sec.max(1) as u64));
+    // 建玉を一度でも確認できたか、現物は建玉を持たないため約定結果のみで判定する
+    let mut has_position = false;
+    loop {
+        tokio::select! {
+            _ = heartbeat_interval.tick() => {
+                let mut w = leg.heartbeat.lock().await;
+                *w = Instant::now();
+            }
+            _ = check_interval.tick() => {
+                // 利確及び損切りの約定数量は自己注文IDで注文を照会して判定する
+                // why: 建玉の情報には自己注文IDが含まれず、建玉から決済注文を識別できない
+                let take_profit_fill = match shared.exchange_client.execution(exit_link_id.clone()).await {
+                    Ok(v) => v.map(|v| (v.cum_exec_qty, v.average_price)).unwrap_or_default(),
+                    Err(e) => {
+                        add_log(&shared.logger, "error", format!("[exit] take profit execution error: {}", e)).await;
+                        continue;
+                    }
+                };
+                let stop_loss_fill = match shared.exchange_client.execution(stop_link_id.clone()).await {
+                    Ok(v) => v.map(|v| (v.cum_exec_qty, v.average_price)).unwrap_or_default(),
+                    Err(e) => {
+                        add_log(&shared.logger, "error", format!("[exit] stop loss execution error: {}", e)).await;
+                        continue;
+                    }
+                };
+                // 建玉に対する損切りは自己注文IDを持たないため、REST APIで建玉の解消を確認する
+                let is_flat = match shared.exchange_client.positions().await {
+                    Ok(v) => {
+                        let is_flat = position::net_position(&v).abs() < f64::EPSILON;
+                        has_position |= !is_flat;
+                        has_position && is_flat
+                    }
+                    Err(e) => {
+                        add_log(&shared.logger, "error", format!("[exit] positions error: {}", e)).await;
+                        false
+                    }
+                };
+
+                let exited = take_profit_fill.0 + stop_loss_fill.0;
+                if exited + f64::EPSILON < entry_qty && !is_flat {
+                    continue;
+                }
+
+                // 損切りで解消した場合は利確注文が残るためキャンセルする
+                if take_profit_fill.0 + f64::EPSILON < entry_qty {
+                    if let Err(e) = shared.exchange_client.cancel_order(exit_link_id.clone()).await {
+                        add_log(&shared.logger, "error", format!("[exit] cancel take profit error: {}", e)).await;
+                    }
+                }
+                // 利確は平均約定価格、残りは損切りの平均約定価格（照会できなければ損切り価格、なければ最終約定価格）で解消したとみなす
+                let take_profit_qty = take_profit_fill.0.min(entry_qty);
+                let take_profit_price = if take_profit_qty > 0.0 { take_profit_fill.1 } else { take_profit };
+                let rest_price = if stop_loss_fill.0 > 0.0 { stop_loss_fill.1 } else { stop_loss.unwrap_or(shared.rx_ticker.borrow().ltp) };
+                let pnl = take_profit_qty * (take_profit_price - average_price) + (entry_qty - take_profit_qty) * (rest_price - average_price);
+                let pnl = match leg.order_config.side {
+                    OrderSide::Buy => pnl,
+                    OrderSide::Sell => -pnl,
+                };
+
+                {
+                    // 利確は板に並ぶためメイカー、損切りは成行のためテイカーとみなす
+                    let mut w = shared.pnl.write().await;
+                    w.record(&pnl::Fill { side: leg.order_config.exit_side(), qty: take_profit_qty, price: take_profit_price, is_maker: true });
+                    w.record(&pnl::Fill { side: leg.order_config.exit_side(), qty: entry_qty - take_profit_qty, price: rest_price, is_maker: false });
+                }
+                book_realized(shared).await;
+                add_log(&shared.logger, "success", format!("[completed] round trip closed, exited size: {}, entry size: {}, flat: {}, realized pnl: {}", exited, entry_qty, is_flat, pnl)).await;
+                return;
+            }
+            _ = shared.cancel_handle.cancelled() => {
+                // 利確注文はキャンセルし、建玉に対する損切りは残す
+                let message = match tokio::time::timeout(STOP_CANCEL_TIMEOUT, shared.exchange_client.cancel_order(exit_link_id.clone())).await {
+                    Ok(Ok(_)) => format!("[stopped] cancelled exit order: {}, stop loss remains: {:?}", exit_link_id, stop_loss),
+                    Ok(Err(e)) => format!("[stopped] cancel exit order: {}, error: {}", exit_link_id, e),
+                    Err(_) => format!("[stopped] cancel exit order: {}, timeout: {:?}", exit_link_id, STOP_CANCEL_TIMEOUT),
+                };
+                add_log(&shared.logger, "info", message).await;
+                return;
+            }
+        }
+    }
+}
@@ -1,8 +1,8 @@
+use std::collections::HashMap;
 use std::env;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use crate::funcs::client;
 use crate::funcs::utils;
 
 mod api;
@@ -23,11 +23,13 @@ pub fn run() {
     };
 
     let use_state = Arc::new(RwLock::new(api::invokers::AppState {
-        controller: client::Controller::default(),
-        workers: None,
-        logger: None,
+        runners: HashMap::new(),
+        risk_ledger: Arc::new(RwLock::new(funcs::risk::Ledger::new())),
     }));
 
+    // 取引時間帯による自動起動・停止
+    tauri::async_runtime::spawn(api::scheduler::run(use_state.clone()));
+
     let app_use_state = use_state.clone();
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
@@ -35,6 +37,9 @@ pub fn run() {
         .invoke_handler(tauri::generate_handler![
             api::invokers::start_controller,
             api::invokers::stop_controller,
+            api::invokers::kill_switch,
+            api::invokers::get_pnl,
+            api::invokers::list_runners,
             api::invokers::post_controller,
             api::invokers::get_controller,
             api::invokers::put_controller,
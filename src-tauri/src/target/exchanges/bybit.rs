@@ -0,0 +1,1460 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crypto_botters::{
+    bybit::{BybitHandlerError, BybitHttpAuth, BybitOption, BybitOptions},
+    generic_api_client::{
+        http::{
+            self, Bytes, HeaderMap, Request, RequestBuilder, RequestConfig, RequestError,
+            RequestHandler, StatusCode,
+        },
+        websocket::WebSocketMessage,
+    },
+    traits::{HandlerOptions, HttpOption},
+    Client, GetOptions,
+};
+use log::{error, info, trace, warn};
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::{
+    board::book::Book,
+    target::{
+        exchanges::{
+            bybit_models::{
+                ApiDefaultResponse, ApiListResponse, ApiOrderResponse, ApiOrderbook,
+                ApiOrderbookResponse, ApiResponse, ApiWalletResponse, InstrumentInfo,
+                OpenOrderInfo, PositionInfo, SpotInstrumentInfo, TickerInfo,
+            },
+            connection,
+            models::{
+                Balance, DataType, ErrorKind, ExchangeError, Execution, Instrument, OpenOrder,
+                OrderClient, OrderParams, OrderSide, OrderType, Orderboard, Position, Ticker,
+                TimeInForce,
+            },
+        },
+        governor::{Backoff, Governor},
+    },
+};
+
+use tokio::sync::{
+    broadcast,
+    mpsc::{Receiver, Sender},
+};
+use tokio::{spawn, task::JoinHandle};
+
+// 未約定注文の取得頁数の上限
+const OPEN_ORDERS_MAX_PAGES: usize = 20;
+// カテゴリ未指定時の既定値、注文及びヘッジ先の価格取得で共有する
+pub const DEFAULT_CATEGORY: &str = "spot";
+// 接続状態の確認間隔
+const CONNECTION_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+// retCodeの分類表
+// https://bybit-exchange.github.io/docs/v5/error
+// 表にないコードはRejectedとして扱い、次の周期で再注文する
+const RET_CODES: &[(i64, ErrorKind, &str)] = &[
+    (10000, ErrorKind::Retryable, "server timeout"),
+    (
+        10002,
+        ErrorKind::Retryable,
+        "request timestamp is out of recv_window",
+    ),
+    (10016, ErrorKind::Retryable, "server error"),
+    (10006, ErrorKind::RateLimited, "too many visits"),
+    (10018, ErrorKind::RateLimited, "exceeded the ip rate limit"),
+    (
+        10429,
+        ErrorKind::RateLimited,
+        "system level frequency protection",
+    ),
+    (10001, ErrorKind::Fatal, "parameter error"),
+    (10003, ErrorKind::Fatal, "api key is invalid"),
+    (10004, ErrorKind::Fatal, "signature error"),
+    (10005, ErrorKind::Fatal, "permission denied"),
+    (10010, ErrorKind::Fatal, "unmatched ip"),
+    (10029, ErrorKind::Fatal, "symbol is not in the whitelist"),
+    (110001, ErrorKind::Rejected, "order does not exist"),
+    (
+        110003,
+        ErrorKind::Rejected,
+        "order price exceeds the allowable range",
+    ),
+    (110004, ErrorKind::Fatal, "insufficient wallet balance"),
+    (110007, ErrorKind::Fatal, "insufficient available balance"),
+    (110012, ErrorKind::Fatal, "insufficient available balance"),
+    (
+        110017,
+        ErrorKind::Rejected,
+        "reduce-only rule not satisfied",
+    ),
+    (
+        110094,
+        ErrorKind::Rejected,
+        "order does not meet minimum order value",
+    ),
+    (170121, ErrorKind::Fatal, "invalid symbol"),
+    (170131, ErrorKind::Fatal, "insufficient balance"),
+];
+
+pub fn classify_ret_code(code: i64) -> ErrorKind {
+    match RET_CODES.iter().find(|(c, _, _)| *c == code) {
+        Some((_, kind, _)) => kind.clone(),
+        None => ErrorKind::Rejected,
+    }
+}
+
+// 建玉情報を建玉に変換し、建玉のない項目は除く
+// 建玉の情報は自己注文IDを含まない
+fn to_positions(list: &[PositionInfo]) -> Vec<Position> {
+    list.iter()
+        .filter_map(|item| {
+            let qty = item.size.parse().unwrap_or_default();
+            if item.side.is_empty() || qty <= 0.0 {
+                return None;
+            }
+
+            // WebSocketは平均取得価格をentryPriceで返す
+            let price = if item.avg_price.is_empty() {
+                &item.entry_price
+            } else {
+                &item.avg_price
+            };
+            Some(Position {
+                symbol: item.symbol.clone(),
+                order_id: String::new(),
+                side: item.side.clone(),
+                qty,
+                price: price.parse().unwrap_or_default(),
+                pnl: item.unrealised_pnl.parse().unwrap_or_default(),
+            })
+        })
+        .collect()
+}
+
+// 統合取引口座以外の口座でaccountTypeにUNIFIEDを指定した場合のエラーか
+// 旧来の口座はパラメータエラーとして返る
+fn is_unsupported_account_type(err: &ExchangeError) -> bool {
+    err.code == 10001 && err.message.to_lowercase().contains("accounttype")
+}
+
+enum ApiRequest<'a> {
+    Get(Vec<(&'a str, String)>),
+    Post(Value),
+}
+
+pub struct BybitClient {
+    client: Client,
+    // 署名付きREST用、レスポンスヘッダを参照するため直接送信する
+    http: http::Client,
+    category: String,
+    symbol: String,
+    governor: Governor,
+    backoff: Backoff,
+}
+
+impl OrderClient for BybitClient {
+    fn new_for_order_client(
+        key: String,
+        secret: String,
+        _passphrase: Option<String>,
+        category: Option<String>,
+        symbol: String,
+    ) -> Self {
+        let mut client = Client::new();
+        client.update_default_option(BybitOption::Key(key));
+        client.update_default_option(BybitOption::Secret(secret));
+
+        BybitClient {
+            client: client.clone(),
+            category: category.unwrap_or(DEFAULT_CATEGORY.to_string()),
+            symbol,
+            http: http::Client::new(),
+            governor: Governor::bybit(),
+            backoff: Backoff::default(),
+        }
+    }
+
+    async fn cancel(&self, order_id: String) -> Result<(), ExchangeError> {
+        let res = self
+            .post_with_retry(
+                "/v5/order/cancel",
+                json!({
+                    "category": self.category.clone(),
+                    "symbol": self.symbol.clone(),
+                    "orderLinkId": order_id
+                }),
+            )
+            .await?;
+
+        trace!("cancel order: {}, response: {:?}", order_id, res);
+
+        Ok(())
+    }
+
+    async fn order(&self, params: &OrderParams) -> Result<String, ExchangeError> {
+        let order_id = params.order_id.as_deref().unwrap_or("");
+        let oside = match params.side {
+            OrderSide::Buy => "Buy",
+            OrderSide::Sell => "Sell",
+        };
+        let otype = match params.order_type {
+            OrderType::Limit => "Limit",
+            OrderType::Market => "Market",
+        };
+        let tif = match params.order_type {
+            // 成行注文はIOCのみ
+            OrderType::Market => "IOC",
+            OrderType::Limit => match params.time_in_force {
+                TimeInForce::Gtc => "GTC",
+                TimeInForce::PostOnly => "PostOnly",
+                TimeInForce::Ioc => "IOC",
+                TimeInForce::Fok => "FOK",
+            },
+        };
+
+        let mut body = json!({
+            "category": self.category.clone(),
+            "symbol": self.symbol.clone(),
+            "orderLinkId": order_id,
+            "side": oside,
+            "orderType": otype,
+            "qty": params.qty,
+            "timeInForce": tif,
+            // 現物は決済専用注文に非対応
+            "reduceOnly": params.reduce_only && self.category != "spot",
+        });
+        if params.order_type == OrderType::Limit {
+            body["price"] = json!(params.price);
+        }
+
+        let res = self.post_with_retry("/v5/order/create", body).await?;
+
+        trace!("place order: {}, response: {:?}", order_id, res);
+
+        Ok(res.result.order_link_id)
+    }
+}
+
+impl BybitClient {
+    pub fn new(
+        key: Option<String>,
+        secret: Option<String>,
+        category: String,
+        symbol: String,
+    ) -> Self {
+        let mut client = Client::new();
+        if let Some(key) = key {
+            client.update_default_option(BybitOption::Key(key));
+        }
+        if let Some(secret) = secret {
+            client.update_default_option(BybitOption::Secret(secret));
+        }
+        let client = client.clone();
+
+        BybitClient {
+            client,
+            category,
+            symbol,
+            http: http::Client::new(),
+            governor: Governor::bybit(),
+            backoff: Backoff::default(),
+        }
+    }
+
+    // 対象銘柄の未約定注文を取得する
+    pub async fn open_orders(&self) -> Result<Vec<OpenOrder>, ExchangeError> {
+        // 1頁の上限は50件、nextPageCursorで次頁を取得する
+        // why: 既定の20件では、それ以降の注文を取りこぼす
+        let mut list: Vec<OpenOrderInfo> = vec![];
+        let mut cursor = String::new();
+        for _ in 0..OPEN_ORDERS_MAX_PAGES {
+            let mut query = vec![
+                ("category", self.category.clone()),
+                ("symbol", self.symbol.clone()),
+                ("openOnly", "0".to_string()),
+                ("limit", "50".to_string()),
+            ];
+            if !cursor.is_empty() {
+                query.push(("cursor", cursor.clone()));
+            }
+            let res = self.get_with_retry("/v5/order/realtime", query).await?;
+
+            let page: Vec<OpenOrderInfo> = match serde_json::from_value(res.result.list) {
+                Ok(v) => v,
+                Err(e) => return Err(ExchangeError::new(ErrorKind::Rejected, -1, e.to_string())),
+            };
+            let is_last = page.is_empty() || res.result.next_page_cursor.is_empty();
+            list.extend(page);
+            if is_last {
+                break;
+            }
+            cursor = res.result.next_page_cursor;
+        }
+
+        Ok(list
+            .iter()
+            .map(|item| OpenOrder {
+                order_id: item.order_link_id.clone(),
+                side: match item.side.as_str() {
+                    "Sell" => OrderSide::Sell,
+                    _ => OrderSide::Buy,
+                },
+                price: item.price.parse().unwrap_or_default(),
+                qty: item.qty.parse().unwrap_or_default(),
+                leaves_qty: item.leaves_qty.parse().unwrap_or_default(),
+            })
+            .collect())
+    }
+
+    // 自己指定の注文IDで注文の約定状況を取得する
+    // 未約定の注文になければ注文履歴を参照する
+    pub async fn execution(&self, order_id: String) -> Result<Option<Execution>, ExchangeError> {
+        for endpoint in ["/v5/order/realtime", "/v5/order/history"] {
+            let res = self
+                .get_with_retry(
+                    endpoint,
+                    vec![
+                        ("category", self.category.clone()),
+                        ("symbol", self.symbol.clone()),
+                        ("orderLinkId", order_id.clone()),
+                    ],
+                )
+                .await?;
+
+            let list: Vec<OpenOrderInfo> = match serde_json::from_value(res.result.list) {
+                Ok(v) => v,
+                Err(e) => return Err(ExchangeError::new(ErrorKind::Rejected, -1, e.to_string())),
+            };
+            if let Some(item) = list.into_iter().next() {
+                return Ok(Some(Execution {
+                    order_id: item.order_link_id,
+                    is_closed: matches!(
+                        item.order_status.as_str(),
+                        "Filled"
+                            | "Cancelled"
+                            | "PartiallyFilledCanceled"
+                            | "Rejected"
+                            | "Deactivated"
+                    ),
+                    cum_exec_qty: item.cum_exec_qty.parse().unwrap_or_default(),
+                    average_price: item.avg_price.parse().unwrap_or_default(),
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+
+    // 対象銘柄の全注文をキャンセルし、キャンセルした注文IDを返す
+    // 自己指定の注文IDがない注文は取引所の注文IDを返す
+    pub async fn cancel_all(&self) -> Result<Vec<String>, ExchangeError> {
+        let res: ApiListResponse = self
+            .request_with_retry(
+                "/v5/order/cancel-all",
+                ApiRequest::Post(json!({
+                    "category": self.category.clone(),
+                    "symbol": self.symbol.clone(),
+                })),
+            )
+            .await?;
+
+        trace!("cancel all: {}, response: {:?}", self.symbol, res);
+
+        Ok(res
+            .result
+            .list
+            .into_iter()
+            .map(|item| {
+                if item.order_link_id.is_empty() {
+                    item.order_id
+                } else {
+                    item.order_link_id
+                }
+            })
+            .collect())
+    }
+
+    // 対象銘柄の建玉を取得する
+    // 現物は建玉を持たないため空を返す
+    pub async fn positions(&self) -> Result<Vec<Position>, ExchangeError> {
+        if self.category == "spot" {
+            return Ok(vec![]);
+        }
+
+        Ok(to_positions(&self.position_list().await?))
+    }
+
+    // 対象銘柄の建玉情報、両建ての場合は方向ごと
+    async fn position_list(&self) -> Result<Vec<PositionInfo>, ExchangeError> {
+        let res = self
+            .get_with_retry(
+                "/v5/position/list",
+                vec![
+                    ("category", self.category.clone()),
+                    ("symbol", self.symbol.clone()),
+                ],
+            )
+            .await?;
+
+        serde_json::from_value(res.result.list)
+            .map_err(|e| ExchangeError::new(ErrorKind::Rejected, -1, e.to_string()))
+    }
+
+    // 発注に使用できる残高を取得する
+    // 統合取引口座（UNIFIED）を前提とし、旧来の口座ではNoneを返して確認を省略する
+    // why: 旧来の口座はaccountTypeにUNIFIEDを指定できず、残高の確認で起動できなくなる
+    // - 現物は基軸通貨・決済通貨それぞれの残高
+    // - デリバティブは証拠金として使用できる残高及び対象銘柄のレバレッジ
+    pub async fn balance(&self) -> Result<Option<Balance>, ExchangeError> {
+        let res: ApiWalletResponse = match self
+            .request_with_retry(
+                "/v5/account/wallet-balance",
+                ApiRequest::Get(vec![("accountType", "UNIFIED".to_string())]),
+            )
+            .await
+        {
+            Ok(v) => v,
+            Err(e) if is_unsupported_account_type(&e) => {
+                warn!("skip balance check for non-unified account: {}", e);
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+        let wallet = res.result.list.into_iter().next().unwrap_or_default();
+        let coin_available = |name: &str| {
+            wallet
+                .coin
+                .iter()
+                .find(|c| c.coin == name)
+                .map(|c| {
+                    c.wallet_balance.parse::<f64>().unwrap_or_default()
+                        - c.locked.parse::<f64>().unwrap_or_default()
+                })
+                .unwrap_or_default()
+        };
+
+        if self.category == "spot" {
+            let (base_coin, quote_coin) = self.spot_coins().await?;
+            return Ok(Some(Balance {
+                is_spot: true,
+                available_margin: 0.0,
+                leverage: 1.0,
+                base_available: coin_available(&base_coin),
+                quote_available: coin_available(&quote_coin),
+                base_coin,
+                quote_coin,
+            }));
+        }
+
+        Ok(Some(Balance {
+            is_spot: false,
+            available_margin: wallet.total_available_balance.parse().unwrap_or_default(),
+            leverage: self.leverage().await?,
+            ..Default::default()
+        }))
+    }
+
+    // 対象銘柄のレバレッジ
+    // 両建ての場合は大きい方を使用する
+    async fn leverage(&self) -> Result<f64, ExchangeError> {
+        Ok(self
+            .position_list()
+            .await?
+            .iter()
+            .filter_map(|item| item.leverage.parse::<f64>().ok())
+            .reduce(f64::max)
+            .unwrap_or(1.0))
+    }
+
+    // 現物銘柄の基軸通貨・決済通貨
+    async fn spot_coins(&self) -> Result<(String, String), ExchangeError> {
+        let res = self
+            .get_with_retry(
+                "/v5/market/instruments-info",
+                vec![
+                    ("category", self.category.clone()),
+                    ("symbol", self.symbol.clone()),
+                ],
+            )
+            .await?;
+
+        let list: Vec<SpotInstrumentInfo> = match serde_json::from_value(res.result.list) {
+            Ok(v) => v,
+            Err(e) => return Err(ExchangeError::new(ErrorKind::Rejected, -1, e.to_string())),
+        };
+
+        match list.into_iter().find(|item| item.symbol == self.symbol) {
+            Some(item) => Ok((item.base_coin, item.quote_coin)),
+            None => Err(ExchangeError::new(
+                ErrorKind::Fatal,
+                -1,
+                format!("instrument is not found: {}", self.symbol),
+            )),
+        }
+    }
+
+    // Disconnected Cancel All (DCP)
+    // privateのWebsocket接続が切れてからtime_window_sec経過すると、取引所側で全注文がキャンセルされる
+    // アカウントでDCPが有効になっている必要がある
+    pub async fn disconnect_cancel_all(&self, time_window_sec: u64) -> Result<(), ExchangeError> {
+        let product = match self.category.as_str() {
+            "spot" => "SPOT",
+            "option" => "OPTIONS",
+            _ => "DERIVATIVES",
+        };
+
+        let res = self
+            .post_with_retry(
+                "/v5/order/disconnected-cancel-all",
+                json!({
+                    "product": product,
+                    // 取引所の許容範囲は3-300秒
+                    "timeWindow": time_window_sec.clamp(3, 300),
+                }),
+            )
+            .await?;
+
+        trace!("disconnected cancel all: {}, response: {:?}", product, res);
+
+        Ok(())
+    }
+
+    // 逆指値の損切りを設定する
+    // - 現物: 成行の条件付き注文（orderFilter: StopOrder）
+    // - デリバティブ: 建玉に対する部分決済の損切り（/v5/position/trading-stop）
+    pub async fn stop_loss(
+        &self,
+        side: OrderSide,
+        qty: f64,
+        trigger_price: f64,
+        order_id: String,
+    ) -> Result<(), ExchangeError> {
+        let oside = match side {
+            OrderSide::Buy => "Buy",
+            OrderSide::Sell => "Sell",
+        };
+
+        let res = if self.category == "spot" {
+            self.post_with_retry(
+                "/v5/order/create",
+                json!({
+                    "category": self.category.clone(),
+                    "symbol": self.symbol.clone(),
+                    "orderLinkId": order_id,
+                    "side": oside,
+                    "orderType": "Market",
+                    "qty": qty,
+                    "triggerPrice": trigger_price,
+                    "orderFilter": "StopOrder",
+                }),
+            )
+            .await?
+        } else {
+            self.post_with_retry(
+                "/v5/position/trading-stop",
+                json!({
+                    "category": self.category.clone(),
+                    "symbol": self.symbol.clone(),
+                    "tpslMode": "Partial",
+                    "positionIdx": 0,
+                    "stopLoss": trigger_price.to_string(),
+                    "slSize": qty.to_string(),
+                    "slOrderType": "Market",
+                    "slTriggerBy": "LastPrice",
+                }),
+            )
+            .await?
+        };
+
+        trace!("stop loss: {}, response: {:?}", order_id, res);
+
+        Ok(())
+    }
+
+    // 署名付きPOSTを送信する
+    async fn post_with_retry(
+        &self,
+        endpoint: &str,
+        body: Value,
+    ) -> Result<ApiOrderResponse, ExchangeError> {
+        self.request_with_retry(endpoint, ApiRequest::Post(body))
+            .await
+    }
+
+    // 署名付きGETを送信する
+    async fn get_with_retry(
+        &self,
+        endpoint: &str,
+        query: Vec<(&str, String)>,
+    ) -> Result<ApiDefaultResponse, ExchangeError> {
+        self.request_with_retry(endpoint, ApiRequest::Get(query))
+            .await
+    }
+
+    // - 送信前にGovernorで送信枠を確保する
+    // - レスポンスヘッダの残数でGovernorを更新する
+    // - RateLimited, Retryableは待機時間を伸ばしながら再試行する
+    // - Rejected, Fatalは即時に返す
+    async fn request_with_retry<R>(
+        &self,
+        endpoint: &str,
+        request: ApiRequest<'_>,
+    ) -> Result<R, ExchangeError>
+    where
+        R: DeserializeOwned + ApiResponse,
+    {
+        let mut attempt = 0;
+        loop {
+            self.governor.acquire(endpoint).await;
+
+            let observed = Arc::new(Mutex::new(None));
+            let res: Result<R, _> = match &request {
+                ApiRequest::Get(query) => {
+                    self.http
+                        .get(
+                            endpoint,
+                            Some(query),
+                            &self.handler::<R, ()>(observed.clone()),
+                        )
+                        .await
+                }
+                ApiRequest::Post(body) => {
+                    self.http
+                        .post(
+                            endpoint,
+                            Some(body.clone()),
+                            &self.handler::<R, Value>(observed.clone()),
+                        )
+                        .await
+                }
+            };
+            let observed = observed.lock().ok().and_then(|v| *v);
+            if let Some(RateLimit {
+                limit,
+                remaining,
+                reset_at_ms,
+            }) = observed
+            {
+                self.governor
+                    .observe(endpoint, limit, remaining, reset_at_ms)
+                    .await;
+            }
+            let err = match res {
+                Ok(res) if res.ret_code() == 0 => return Ok(res),
+                Ok(res) => ExchangeError::new(
+                    classify_ret_code(res.ret_code()),
+                    res.ret_code(),
+                    res.ret_msg(),
+                ),
+                Err(e) => to_exchange_error(e),
+            };
+
+            if !err.is_retryable() || attempt >= self.backoff.max_retry {
+                return Err(err);
+            }
+
+            let wait = self.backoff.delay(attempt);
+            if err.kind == ErrorKind::RateLimited {
+                self.governor.penalize(endpoint, wait).await;
+            }
+            warn!(
+                "retry {} after {:?}, attempt: {}, error: {}",
+                endpoint, wait, attempt, err
+            );
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+        }
+    }
+
+    // 署名付きRESTのハンドラ
+    fn handler<'a, R, B>(
+        &self,
+        observed: Arc<Mutex<Option<RateLimit>>>,
+    ) -> RateLimitObserver<<BybitOption as HttpOption<'a, R, B>>::RequestHandler>
+    where
+        BybitOption: HttpOption<'a, R, B>,
+    {
+        let mut options = GetOptions::<BybitOptions>::default_options(&self.client).clone();
+        options.update(BybitOption::HttpAuth(BybitHttpAuth::V3AndAbove));
+
+        RateLimitObserver {
+            inner: <BybitOption as HttpOption<'a, R, B>>::request_handler(options),
+            observed,
+        }
+    }
+
+    pub async fn public_ticker(
+        &self,
+        // websocket用
+        tx_ws_ticker: Sender<Ticker>,
+        // rest用取得依頼
+        // why: websocket非実装の場合、必要に応じてRestRequestを実行する
+        // API Limitの観点から必要に応じてのみ実行する
+        mut rx_rest_ticker: Receiver<()>,
+        // rest用取得結果通知用
+        // why: websocket非実装の場合、必要に応じてRestRequest結果を送信する
+        tx_rest_ticker: broadcast::Sender<Ticker>,
+        // 接続状態の通知用
+        tx_connection: Sender<connection::Event>,
+    ) -> Result<JoinHandle<()>, String> {
+        let client = self.client.clone();
+        let category = self.category.clone();
+        let symbol = self.symbol.clone();
+
+        let handler = spawn(async move {
+            let url = format!("/v5/public/{}", category.clone());
+            let set_symbol = symbol.clone();
+
+            let on_message = move |message: Value| {
+                let data = message.clone()["data"].take();
+
+                trace!("ticker raw data: {}", data);
+
+                let ltp = match data["lastPrice"].as_str() {
+                    Some(v) => v.parse::<f64>().unwrap_or_default(),
+                    None => return,
+                };
+                let v24 = match data["volume24h"].as_str() {
+                    Some(v) => v.parse::<f64>().unwrap_or_default(),
+                    None => return,
+                };
+                let bid = match data["bid1Price"].as_str() {
+                    Some(v) => v.parse::<f64>().unwrap_or_default(),
+                    None => return,
+                };
+                let ask = match data["ask1Price"].as_str() {
+                    Some(v) => v.parse::<f64>().unwrap_or_default(),
+                    None => return,
+                };
+
+                match tx_ws_ticker.try_send(Ticker::new(set_symbol.clone(), ltp, v24, ask, bid)) {
+                    Ok(()) => (),
+                    Err(e) => {
+                        error!("ticker send error: {}", e);
+                    }
+                };
+            };
+
+            let rest = async {
+                while rx_rest_ticker.recv().await.is_some() {
+                    // rest用取得依頼
+                    // 実取得
+                    let ltp = 0.0;
+                    let v24 = 0.0;
+                    let bid = 0.0;
+                    let ask = 0.0;
+
+                    // rest用取得結果通知
+                    tx_rest_ticker
+                        .send(Ticker::new(symbol.clone(), ltp, v24, ask, bid))
+                        .unwrap();
+                }
+            };
+
+            tokio::join!(
+                supervise(
+                    &client,
+                    &url,
+                    vec![format!("tickers.{}", symbol.clone())],
+                    true,
+                    connection::Config::public(),
+                    connection::Link::new(connection::TICKER),
+                    tx_connection,
+                    on_message,
+                ),
+                rest
+            );
+        });
+
+        Ok(handler)
+    }
+
+    pub async fn public_orderboard(
+        &self,
+        depth: Option<i64>,
+        // websocket用
+        tx_ws_orderboard: Sender<Orderboard>,
+        // rest用取得依頼
+        // why: websocket非実装の場合、必要に応じてRestRequestを実行する
+        // API Limitの観点から必要に応じてのみ実行する
+        mut rx_rest_orderboard: Receiver<()>,
+        // rest用取得結果通知用
+        // why: websocket非実装の場合、必要に応じてRestRequest結果を送信する
+        tx_rest_orderboard: broadcast::Sender<Orderboard>,
+        // 接続状態の通知用
+        tx_connection: Sender<connection::Event>,
+    ) -> Result<JoinHandle<()>, String> {
+        let client = self.client.clone();
+        let category = self.category.clone();
+        let symbol = self.symbol.clone();
+        let set_depth = depth.unwrap_or(200);
+        let cloned_tx_ws_orderboard = tx_ws_orderboard.clone();
+
+        let handler = spawn(async move {
+            let url = format!("/v5/public/{}", category.clone());
+            let set_symbol = symbol.clone();
+
+            let on_message = move |message: Value| {
+                let data = message.clone()["data"].take();
+
+                trace!("orderboard raw data: {}", data);
+
+                let data_type = match message.clone()["type"].as_str() {
+                    Some(v) => match v {
+                        "snapshot" => DataType::Snapshot,
+                        "delta" => DataType::UpdateDelta,
+                        _ => DataType::Snapshot,
+                    },
+                    None => return,
+                };
+                let get_orderboards: ApiOrderbook = match serde_json::from_value(data) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        trace!("error: {}", e);
+                        ApiOrderbook {
+                            s: "".to_owned(),
+                            b: vec![], // Bids [price, size]
+                            a: vec![], // Asks [price, size]
+                            u: 0,      // Update ID
+                            seq: 0,    // Sequence number
+                        }
+                    }
+                };
+
+                // create generic orderboard
+                match tx_ws_orderboard.try_send(Orderboard::new(
+                    data_type.clone(),
+                    set_symbol.clone(),
+                    to_books(get_orderboards.a),
+                    to_books(get_orderboards.b),
+                    None,
+                    Some(get_orderboards.u),
+                )) {
+                    Ok(()) => (),
+                    Err(e) => {
+                        error!("orderboard send error: {}", e);
+                    }
+                };
+            };
+
+            let rest = async {
+                while rx_rest_orderboard.recv().await.is_some() {
+                    // rest用取得依頼
+                    // 板の再取得、Websocketと同じチャネルへSnapshotとして送信し板を差し替える
+                    // why: 差分の取りこぼしで壊れた板は、以降の差分を適用しても復旧しない
+                    let o = match orderboard(&client, category.clone(), symbol.clone(), set_depth)
+                        .await
+                    {
+                        Ok(v) => v,
+                        Err(e) => {
+                            error!("orderboard fetch error: {}", e);
+                            continue;
+                        }
+                    };
+                    if let Err(e) = cloned_tx_ws_orderboard.send(o.clone()).await {
+                        error!("orderboard send error: {}", e);
+                    }
+
+                    // rest用取得結果通知
+                    // 受信側がない場合は送信しない
+                    let _ = tx_rest_orderboard.send(o);
+                }
+            };
+
+            tokio::join!(
+                supervise(
+                    &client,
+                    &url,
+                    vec![format!("orderbook.{}.{}", set_depth, symbol.clone())],
+                    false,
+                    connection::Config::public(),
+                    connection::Link::new(connection::ORDERBOARD),
+                    tx_connection,
+                    on_message,
+                ),
+                rest
+            );
+        });
+
+        Ok(handler)
+    }
+
+    pub async fn private_position(
+        &self,
+        // websocket用
+        tx_ws_position: Sender<Vec<Position>>,
+        // rest用取得依頼
+        // why: websocket非実装の場合、必要に応じてRestRequestを実行する
+        // API Limitの観点から必要に応じてのみ実行する
+        mut rx_rest_position: Receiver<()>,
+        // rest用取得結果通知用
+        // why: websocket非実装の場合、必要に応じてRestRequest結果を送信する
+        tx_rest_position: broadcast::Sender<Vec<Position>>,
+        // 接続状態の通知用
+        tx_connection: Sender<connection::Event>,
+    ) -> Result<JoinHandle<()>, String> {
+        let client = self.client.clone();
+        let set_symbol = self.symbol.clone();
+
+        let handler = spawn(async move {
+            let url = "/v5/private";
+
+            let on_message = move |message: Value| {
+                let data = message.clone()["data"].take();
+
+                trace!("position raw data: {}", data);
+
+                let get_positions: Vec<PositionInfo> = match serde_json::from_value(data) {
+                    Ok(v) => v,
+                    Err(e) => {
+                        error!("position parse error: {}", e);
+                        return;
+                    }
+                };
+
+                // 対象銘柄の更新がなければ送信しない
+                // 建玉が解消した場合は空の建玉を送信する
+                let get_positions = get_positions
+                    .into_iter()
+                    .filter(|p| p.symbol == set_symbol)
+                    .collect::<Vec<PositionInfo>>();
+                if get_positions.is_empty() {
+                    return;
+                }
+                let use_positions = to_positions(&get_positions);
+
+                match tx_ws_position.try_send(use_positions) {
+                    Ok(()) => (),
+                    Err(e) => {
+                        error!("position send error: {}", e);
+                    }
+                };
+            };
+
+            let rest = async {
+                while rx_rest_position.recv().await.is_some() {
+                    // rest用取得依頼
+                    // 実取得
+
+                    // rest用取得結果通知
+                    tx_rest_position.send(vec![]).unwrap();
+                }
+            };
+
+            tokio::join!(
+                supervise(
+                    &client,
+                    url,
+                    vec!["position".to_owned()],
+                    true,
+                    connection::Config::private(),
+                    connection::Link::new(connection::POSITION),
+                    tx_connection,
+                    on_message,
+                ),
+                rest
+            );
+        });
+
+        Ok(handler)
+    }
+}
+
+// WebSocketへ接続し、切断時は再接続する
+// - 接続の失敗は待機時間を伸ばしながら上限なく再試行する
+// - 購読は接続ごとに送信されるため、再接続後も同じトピックを受信する
+// - 受信が途絶えた場合は接続を破棄して接続し直す
+// why: 接続の失敗でタスクが終了すると、注文タスクは古い板のまま注文を残し続ける
+#[allow(clippy::too_many_arguments)]
+async fn supervise<H>(
+    client: &Client,
+    url: &str,
+    topics: Vec<String>,
+    auth: bool,
+    config: connection::Config,
+    mut link: connection::Link,
+    tx_connection: Sender<connection::Event>,
+    handler: H,
+) where
+    H: FnMut(Value) + Clone + Send + 'static,
+{
+    let notify = |event: Option<connection::Event>| {
+        let Some(event) = event else {
+            return;
+        };
+        match &event {
+            connection::Event::Disconnected { stream, reason } => {
+                warn!("websocket disconnected: {}, {}", stream, reason)
+            }
+            connection::Event::Reconnected { stream } => {
+                info!("websocket reconnected: {}", stream)
+            }
+        }
+        if let Err(e) = tx_connection.try_send(event) {
+            error!("connection event send error: {}", e);
+        }
+    };
+
+    let mut attempt = 0;
+    loop {
+        let received_at = Arc::new(Mutex::new(Instant::now()));
+        let on_message = {
+            let (mut handler, received_at) = (handler.clone(), received_at.clone());
+            move |message: Value| {
+                if let Ok(mut w) = received_at.lock() {
+                    *w = Instant::now();
+                }
+                // pingの応答は受信の確認のみとする
+                if message.get("op").is_some() {
+                    return;
+                }
+                handler(message);
+            }
+        };
+        let options = [
+            BybitOption::WebSocketTopics(topics.clone()),
+            BybitOption::WebSocketAuth(auth),
+        ];
+        let connection = match client.websocket(url, on_message, options).await {
+            Ok(v) => v,
+            Err(e) => {
+                notify(link.disconnected(format!("connect error: {}", e)));
+                tokio::time::sleep(config.backoff.delay(attempt)).await;
+                attempt = attempt.saturating_add(1);
+                continue;
+            }
+        };
+        attempt = 0;
+        notify(link.connected());
+
+        let reconnect_state = connection.reconnect_state();
+        let mut interval = tokio::time::interval(CONNECTION_CHECK_INTERVAL);
+        let mut ping_at = Instant::now();
+        loop {
+            interval.tick().await;
+
+            // 通信エラーによるライブラリ内の再接続中は切断として扱う
+            if reconnect_state.is_reconnecting() {
+                notify(link.disconnected("connection error, reconnecting".to_string()));
+                continue;
+            }
+            notify(link.connected());
+
+            if ping_at.elapsed() >= config.ping_interval {
+                ping_at = Instant::now();
+                let ping = WebSocketMessage::Text(json!({ "op": "ping" }).to_string());
+                if let Err(e) = connection.send_message(ping).await {
+                    warn!("websocket ping error: {}", e);
+                }
+            }
+
+            let elapsed = received_at.lock().map(|v| v.elapsed()).unwrap_or_default();
+            if config.idle_timeout.is_some_and(|timeout| elapsed > timeout) {
+                notify(link.disconnected(format!("no message for {:?}", elapsed)));
+                break;
+            }
+        }
+    }
+}
+
+// レスポンスヘッダのレート制限
+// X-Bapi-Limit, X-Bapi-Limit-Status, X-Bapi-Limit-Reset-Timestamp(ms)
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RateLimit {
+    limit: u32,
+    remaining: u32,
+    reset_at_ms: i64,
+}
+
+impl RateLimit {
+    // レート制限の対象外のエンドポイントはヘッダがない
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = |name: &str| headers.get(name)?.to_str().ok()?.parse::<i64>().ok();
+
+        Some(RateLimit {
+            limit: value("X-Bapi-Limit")?.try_into().ok()?,
+            remaining: value("X-Bapi-Limit-Status")?.try_into().ok()?,
+            reset_at_ms: value("X-Bapi-Limit-Reset-Timestamp")?,
+        })
+    }
+}
+
+// Bybitのハンドラを包み、レスポンスヘッダのレート制限を読み取る
+// 署名及び本文の解析はBybitのハンドラに委譲する
+// why: crypto_bottersのBybitハンドラはレスポンスヘッダを破棄する
+struct RateLimitObserver<H> {
+    inner: H,
+    observed: Arc<Mutex<Option<RateLimit>>>,
+}
+
+impl<B, H: RequestHandler<B>> RequestHandler<B> for RateLimitObserver<H> {
+    type Successful = H::Successful;
+    type Unsuccessful = H::Unsuccessful;
+    type BuildError = H::BuildError;
+
+    fn request_config(&self) -> RequestConfig {
+        self.inner.request_config()
+    }
+
+    fn build_request(
+        &self,
+        builder: RequestBuilder,
+        request_body: &Option<B>,
+        attempt_count: u8,
+    ) -> Result<Request, Self::BuildError> {
+        self.inner
+            .build_request(builder, request_body, attempt_count)
+    }
+
+    fn handle_response(
+        &self,
+        status: StatusCode,
+        headers: HeaderMap,
+        response_body: Bytes,
+    ) -> Result<Self::Successful, Self::Unsuccessful> {
+        if let Ok(mut w) = self.observed.lock() {
+            *w = RateLimit::from_headers(&headers);
+        }
+        self.inner.handle_response(status, headers, response_body)
+    }
+}
+
+// HTTP層のエラーを分類する
+// - 通信の失敗は再試行対象
+// - 403はIP制限のため待機対象
+// - 4xx/5xxの本文にretCodeがあれば分類表を使用する
+fn to_exchange_error(e: RequestError<&'static str, BybitHandlerError>) -> ExchangeError {
+    match e {
+        RequestError::SendRequest(e) | RequestError::ReceiveResponse(e) => {
+            ExchangeError::new(ErrorKind::Retryable, -1, e.to_string())
+        }
+        RequestError::BuildRequestError(e) => {
+            ExchangeError::new(ErrorKind::Fatal, -1, e.to_string())
+        }
+        RequestError::ResponseHandleError(BybitHandlerError::IpBan(v)) => {
+            ExchangeError::new(ErrorKind::RateLimited, 403, v.to_string())
+        }
+        RequestError::ResponseHandleError(BybitHandlerError::ApiError(v)) => {
+            match v["retCode"].as_i64() {
+                Some(code) => ExchangeError::new(classify_ret_code(code), code, v.to_string()),
+                None => ExchangeError::new(ErrorKind::Retryable, -1, v.to_string()),
+            }
+        }
+        RequestError::ResponseHandleError(BybitHandlerError::ParseError) => ExchangeError::new(
+            ErrorKind::Rejected,
+            -1,
+            "failed to parse response".to_string(),
+        ),
+    }
+}
+
+pub async fn instruments(category: String) -> Result<Vec<Instrument>, String> {
+    let client = Client::new();
+
+    // public GET
+    let res: ApiDefaultResponse = match client
+        .get(
+            "/v5/market/instruments-info",
+            Some(&[("category", category)]),
+            [BybitOption::Default],
+        )
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => return Err(e.to_string()),
+    };
+    if res.ret_code != 0 {
+        return Err(res.ret_msg);
+    }
+
+    let list: Vec<InstrumentInfo> = serde_json::from_value(res.result.list).unwrap();
+
+    Ok(list
+        .iter()
+        .map(|item| Instrument {
+            symbol: item.symbol.clone(),
+            ltp: 0.0,
+            volume24h: 0.0,
+            price_tick: item.price_filter.tick_size.parse().unwrap(),
+            size_tick: item.lot_size_filter.qty_step.parse().unwrap(),
+            size_min: item.lot_size_filter.min_order_qty.parse().unwrap(),
+        })
+        .collect())
+}
+
+// 板のREST取得
+async fn orderboard(
+    client: &Client,
+    category: String,
+    symbol: String,
+    depth: i64,
+) -> Result<Orderboard, String> {
+    // public GET
+    let res: ApiOrderbookResponse = match client
+        .get(
+            "/v5/market/orderbook",
+            Some(&[
+                ("category", category),
+                ("symbol", symbol.clone()),
+                ("limit", depth.to_string()),
+            ]),
+            [BybitOption::Default],
+        )
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => return Err(e.to_string()),
+    };
+    if res.ret_code != 0 {
+        return Err(res.ret_msg);
+    }
+
+    let book = match res.result {
+        Some(v) => v,
+        None => return Err(format!("orderboard is empty for {}", symbol)),
+    };
+    Ok(Orderboard::new(
+        DataType::Snapshot,
+        symbol,
+        to_books(book.a),
+        to_books(book.b),
+        None,
+        Some(book.u),
+    ))
+}
+
+// [price, size]の文字列を板に変換する
+fn to_books(levels: Vec<[String; 2]>) -> Vec<Book> {
+    levels
+        .iter()
+        .map(|level| Book {
+            price: level[0].parse().unwrap_or_default(),
+            size: level[1].parse().unwrap_or_default(),
+        })
+        .collect()
+}
+
+pub async fn ticker(category: String, symbol: String) -> Result<Ticker, String> {
+    let client = Client::new();
+    // public GET
+    let res: ApiDefaultResponse = match client
+        .get(
+            "/v5/market/tickers",
+            Some(&[("category", category), ("symbol", symbol.clone())]),
+            [BybitOption::Default],
+        )
+        .await
+    {
+        Ok(res) => res,
+        Err(e) => return Err(e.to_string()),
+    };
+    if res.ret_code != 0 {
+        return Err(res.ret_msg);
+    }
+
+    let tickers = match serde_json::from_value::<Vec<TickerInfo>>(res.result.list) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(e.to_string());
+        }
+    };
+    let target_ticker = match tickers.iter().find(|t| t.symbol == symbol) {
+        Some(v) => v,
+        None => {
+            return Err(format!("ticker is not match for {}", symbol).to_string());
+        }
+    };
+    let ticker = Ticker {
+        symbol: target_ticker.symbol.clone(),
+        ltp: target_ticker.last_price.parse().unwrap_or_default(),
+        volume24h: target_ticker.volume_24h.parse().unwrap_or_default(),
+        best_ask: target_ticker.ask1_price.parse().unwrap_or_default(),
+        best_bid: target_ticker.bid1_price.parse().unwrap_or_default(),
+    };
+
+    Ok(ticker)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::funcs::risk;
+
+    #[test]
+    fn test_rate_limit_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Bapi-Limit", "10".parse().unwrap());
+        headers.insert("X-Bapi-Limit-Status", "7".parse().unwrap());
+        assert_eq!(RateLimit::from_headers(&headers), None);
+
+        headers.insert(
+            "X-Bapi-Limit-Reset-Timestamp",
+            "1700000000123".parse().unwrap(),
+        );
+        assert_eq!(
+            RateLimit::from_headers(&headers),
+            Some(RateLimit {
+                limit: 10,
+                remaining: 7,
+                reset_at_ms: 1_700_000_000_123,
+            })
+        );
+    }
+
+    #[test]
+    fn test_classify_ret_code() {
+        assert_eq!(classify_ret_code(10006), ErrorKind::RateLimited);
+        assert_eq!(classify_ret_code(10002), ErrorKind::Retryable);
+        assert_eq!(classify_ret_code(110007), ErrorKind::Fatal);
+        assert_eq!(classify_ret_code(170121), ErrorKind::Fatal);
+        assert_eq!(classify_ret_code(110001), ErrorKind::Rejected);
+        // 表にないコード
+        assert_eq!(classify_ret_code(999999), ErrorKind::Rejected);
+    }
+
+    #[test]
+    fn test_to_positions_from_websocket() {
+        // WebSocketの建玉はavgPriceを持たず、解消した建玉はsideが空で届く
+        let list: Vec<PositionInfo> = serde_json::from_value(json!([
+            {
+                "symbol": "BTCUSDT",
+                "side": "Sell",
+                "size": "0.5",
+                "entryPrice": "60000",
+                "unrealisedPnl": "-1.5",
+                "leverage": "10",
+                "positionIdx": 0
+            },
+            {
+                "symbol": "BTCUSDT",
+                "side": "",
+                "size": "0",
+                "entryPrice": "0",
+                "leverage": "10"
+            }
+        ]))
+        .unwrap();
+
+        let positions = to_positions(&list);
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].side, "Sell");
+        assert_eq!(positions[0].qty, 0.5);
+        assert_eq!(positions[0].price, 60000.0);
+    }
+
+    #[test]
+    fn test_position_list_trips_risk_limit() {
+        // /v5/position/listの建玉から発注前の建玉上限を判定する
+        let res: ApiDefaultResponse = serde_json::from_value(json!({
+            "retCode": 0,
+            "retMsg": "OK",
+            "result": {
+                "category": "linear",
+                "list": [
+                    {
+                        "symbol": "BTCUSDT",
+                        "side": "Buy",
+                        "size": "0.8",
+                        "avgPrice": "60000",
+                        "positionValue": "48000",
+                        "unrealisedPnl": "12.5",
+                        "leverage": "10",
+                        "positionIdx": 0
+                    }
+                ],
+                "nextPageCursor": ""
+            },
+            "retExtInfo": {},
+            "time": 1
+        }))
+        .unwrap();
+        let list: Vec<PositionInfo> = serde_json::from_value(res.result.list).unwrap();
+        let exposure = risk::Exposure::from_positions(&to_positions(&list), 0, 60000.0);
+        assert_eq!(exposure.net_position, 0.8);
+
+        let config = risk::Config {
+            max_position: 1.0,
+            ..Default::default()
+        };
+        let params = |side: OrderSide| OrderParams {
+            order_id: None,
+            side,
+            price: 60000.0,
+            qty: 0.5,
+            order_type: OrderType::Limit,
+            time_in_force: TimeInForce::Gtc,
+            reduce_only: false,
+        };
+        let ledger = risk::Ledger::new();
+        let event = config
+            .check("BTCUSDT", &params(OrderSide::Buy), &exposure, &ledger)
+            .unwrap();
+        assert_eq!(event.rule, risk::Rule::Position);
+        // 建玉を減らす注文は許可する
+        assert_eq!(
+            config.check("BTCUSDT", &params(OrderSide::Sell), &exposure, &ledger),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_unsupported_account_type() {
+        let err = ExchangeError::new(
+            ErrorKind::Fatal,
+            10001,
+            "accountType only support CONTRACT and SPOT".to_string(),
+        );
+        assert!(is_unsupported_account_type(&err));
+
+        let err = ExchangeError::new(ErrorKind::Fatal, 10001, "params error".to_string());
+        assert!(!is_unsupported_account_type(&err));
+    }
+
+    #[test]
+    fn test_cancel_all_response() {
+        let res: ApiListResponse = serde_json::from_value(json!({
+            "retCode": 0,
+            "retMsg": "OK",
+            "result": {
+                "list": [
+                    {"orderId": "1", "orderLinkId": "bybit_BTCUSDT_board4rs"},
+                    {"orderId": "2", "orderLinkId": ""}
+                ],
+                "success": "1"
+            },
+            "retExtInfo": {},
+            "time": 1
+        }))
+        .unwrap();
+
+        assert_eq!(res.result.list.len(), 2);
+        assert_eq!(res.result.list[0].order_link_id, "bybit_BTCUSDT_board4rs");
+        assert_eq!(res.result.list[1].order_id, "2");
+    }
+
+    #[tokio::test]
+    async fn test_instruments() {
+        let category = "linear".to_string();
+        let instruments = instruments(category).await.unwrap();
+        println!("{:?}", instruments);
+    }
+
+    #[tokio::test]
+    async fn test_ticker() {
+        let category = "linear".to_string();
+        let symbol = "BTCUSDT".to_string();
+        let ticker = match ticker(category, symbol).await {
+            Ok(v) => v,
+            Err(e) => {
+                println!("error: {}", e);
+                Ticker::default()
+            }
+        };
+
+        println!("{:?}", ticker);
+    }
+}
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use crate::target::governor::Backoff;
+
+// 購読の識別名
+pub const TICKER: &str = "ticker";
+pub const ORDERBOARD: &str = "orderboard";
+pub const POSITION: &str = "position";
+
+// WebSocket接続の状態の通知
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    // 接続が途絶えた、再接続するまで受信データは欠落する
+    Disconnected { stream: String, reason: String },
+    // 再接続して購読を再開した
+    Reconnected { stream: String },
+}
+
+// 接続の監視設定
+#[derive(Debug, Clone)]
+pub struct Config {
+    // 接続失敗時の再試行間隔、回数の上限なく再試行する
+    pub backoff: Backoff,
+    // 受信がない期間の上限、超えた場合は接続し直す
+    // 受信頻度の低い購読はNoneとする
+    pub idle_timeout: Option<Duration>,
+    // 接続維持のためのping送信間隔
+    pub ping_interval: Duration,
+}
+
+impl Config {
+    pub fn public() -> Self {
+        Config {
+            idle_timeout: Some(Duration::from_secs(30)),
+            ..Config::private()
+        }
+    }
+
+    pub fn private() -> Self {
+        Config {
+            backoff: Backoff {
+                base_ms: 1_000,
+                max_ms: 30_000,
+                ..Default::default()
+            },
+            idle_timeout: None,
+            ping_interval: Duration::from_secs(20),
+        }
+    }
+}
+
+// 接続状態の遷移を追跡し、境界でのみ通知を返す
+// 初回の接続は通知しない
+pub struct Link {
+    stream: String,
+    is_connected: bool,
+    // 一度でも切断を通知したか
+    is_notified: bool,
+}
+
+impl Link {
+    pub fn new(stream: &str) -> Self {
+        Link {
+            stream: stream.to_string(),
+            is_connected: false,
+            is_notified: false,
+        }
+    }
+
+    pub fn connected(&mut self) -> Option<Event> {
+        if self.is_connected {
+            return None;
+        }
+        self.is_connected = true;
+
+        if !self.is_notified {
+            return None;
+        }
+        Some(Event::Reconnected {
+            stream: self.stream.clone(),
+        })
+    }
+
+    pub fn disconnected(&mut self, reason: String) -> Option<Event> {
+        // 初回の接続失敗も通知する
+        if !self.is_connected && self.is_notified {
+            return None;
+        }
+        self.is_connected = false;
+        self.is_notified = true;
+
+        Some(Event::Disconnected {
+            stream: self.stream.clone(),
+            reason,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_notifies_only_on_boundary() {
+        let mut link = Link::new("orderboard");
+
+        assert_eq!(link.connected(), None);
+        assert_eq!(
+            link.disconnected("closed".to_string()),
+            Some(Event::Disconnected {
+                stream: "orderboard".to_string(),
+                reason: "closed".to_string()
+            })
+        );
+        // 再接続の失敗は重ねて通知しない
+        assert_eq!(link.disconnected("refused".to_string()), None);
+        assert_eq!(
+            link.connected(),
+            Some(Event::Reconnected {
+                stream: "orderboard".to_string()
+            })
+        );
+        assert_eq!(link.connected(), None);
+
+        // 初回の接続失敗は通知し、接続した時点で再開を通知する
+        let mut link = Link::new("ticker");
+        assert!(link.disconnected("refused".to_string()).is_some());
+        assert!(link.connected().is_some());
+    }
+}
@@ -0,0 +1,4 @@
+pub mod bybit;
+pub mod bybit_models;
+pub mod connection;
+pub mod models;
@@ -0,0 +1,349 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    board::book::Book,
+    target::{
+        exchange::{Config, ExchangeName},
+        exchanges::bybit::BybitClient,
+    },
+};
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub enum BookSide {
+    #[default]
+    #[serde(rename = "bid")]
+    Bid,
+    #[serde(rename = "ask")]
+    Ask,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum OrderSide {
+    #[default]
+    #[serde(rename = "buy")]
+    Buy,
+    #[serde(rename = "sell")]
+    Sell,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum OrderType {
+    #[default]
+    #[serde(rename = "limit")]
+    Limit,
+    // 価格指定なし、即時約定
+    #[serde(rename = "market")]
+    Market,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub enum TimeInForce {
+    #[default]
+    #[serde(rename = "gtc")]
+    Gtc,
+    // メイカー注文のみ、約定する価格であれば取引所がキャンセルする
+    #[serde(rename = "post_only")]
+    PostOnly,
+    // 即時約定分のみ、残りはキャンセル
+    #[serde(rename = "ioc")]
+    Ioc,
+    // 全量即時約定、できなければ全てキャンセル
+    #[serde(rename = "fok")]
+    Fok,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct OrderParams {
+    pub order_id: Option<String>,
+    pub side: OrderSide,
+    pub price: f64,
+    pub qty: f64,
+    pub order_type: OrderType,
+    pub time_in_force: TimeInForce,
+    // 建玉を減らす方向のみ約定させる
+    pub reduce_only: bool,
+}
+
+// 取引所エラーの分類
+// - RateLimited: 送信頻度超過、待機後に再試行する
+// - Retryable: 一時的な失敗（タイムスタンプずれ、通信断など）、再試行する
+// - Rejected: 当該注文のみの失敗、次の周期で再注文する
+// - Fatal: 設定や口座の問題、再試行しても解消しないためRunnerを停止する
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub enum ErrorKind {
+    RateLimited,
+    Retryable,
+    #[default]
+    Rejected,
+    Fatal,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ExchangeError {
+    pub kind: ErrorKind,
+    // 取引所の返すエラーコード、通信エラーなどコードがない場合は-1
+    pub code: i64,
+    pub message: String,
+}
+
+impl ExchangeError {
+    pub fn new(kind: ErrorKind, code: i64, message: String) -> Self {
+        ExchangeError {
+            kind,
+            code,
+            message,
+        }
+    }
+
+    pub fn is_fatal(&self) -> bool {
+        self.kind == ErrorKind::Fatal
+    }
+
+    pub fn is_retryable(&self) -> bool {
+        matches!(self.kind, ErrorKind::RateLimited | ErrorKind::Retryable)
+    }
+}
+
+impl std::fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{:?}] code: {}, {}", self.kind, self.code, self.message)
+    }
+}
+
+pub trait OrderClient {
+    fn new_for_order_client(
+        key: String,
+        secret: String,
+        passphrase: Option<String>,
+        category: Option<String>,
+        symbol: String,
+    ) -> Self;
+    async fn cancel(&self, order_id: String) -> Result<(), ExchangeError>;
+    async fn order(&self, params: &OrderParams) -> Result<String, ExchangeError>;
+}
+
+// 複数の取引所情報を管理する型（enumで各取引所のオブジェクトを保持）
+pub enum ToExchange {
+    None,
+    Bybit(BybitClient),
+    // Bitbank(BitbankClient),
+}
+
+impl ToExchange {
+    pub fn create_client(exchange_config: &Config, symbol: String) -> Self {
+        match exchange_config.name {
+            ExchangeName::Bybit => {
+                let client = <BybitClient as OrderClient>::new_for_order_client(
+                    exchange_config.key.clone(),
+                    exchange_config.secret.clone(),
+                    None,
+                    exchange_config.category.clone(),
+                    symbol.clone(),
+                );
+                ToExchange::Bybit(client)
+            }
+            ExchangeName::Bitbank => ToExchange::None,
+            ExchangeName::Bitflyer => ToExchange::None,
+        }
+    }
+    pub async fn cancel_order(&self, order_id: String) -> Result<(), ExchangeError> {
+        match self {
+            ToExchange::Bybit(client) => client.cancel(order_id).await,
+            // ToExchange::Bitbank(client) => client.cancel(order_id),
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn place_order(&self, params: &OrderParams) -> Result<String, ExchangeError> {
+        match self {
+            ToExchange::Bybit(client) => client.order(params).await, // ToExchange::Bitbank(client) => client.order(order_id, price, qty),
+            _ => Ok("".to_string()),
+        }
+    }
+
+    // 対象銘柄の未約定注文を取得する
+    pub async fn open_orders(&self) -> Result<Vec<OpenOrder>, ExchangeError> {
+        match self {
+            ToExchange::Bybit(client) => client.open_orders().await,
+            _ => Ok(vec![]),
+        }
+    }
+
+    // 自己指定の注文IDで注文の約定状況を取得する
+    // 見つからない場合及び非対応の取引所はNoneを返す
+    pub async fn execution(&self, order_id: String) -> Result<Option<Execution>, ExchangeError> {
+        match self {
+            ToExchange::Bybit(client) => client.execution(order_id).await,
+            _ => Ok(None),
+        }
+    }
+
+    // 対象銘柄の全注文をキャンセルする
+    // 非対応の取引所は何もしない
+    pub async fn cancel_all(&self) -> Result<Vec<String>, ExchangeError> {
+        match self {
+            ToExchange::Bybit(client) => client.cancel_all().await,
+            _ => Ok(vec![]),
+        }
+    }
+
+    // 対象銘柄の建玉をRESTで取得する
+    pub async fn positions(&self) -> Result<Vec<Position>, ExchangeError> {
+        match self {
+            ToExchange::Bybit(client) => client.positions().await,
+            _ => Ok(vec![]),
+        }
+    }
+
+    // 発注に使用できる残高を取得する
+    // 非対応の取引所及び口座はNoneを返し、確認を省略する
+    pub async fn balance(&self) -> Result<Option<Balance>, ExchangeError> {
+        match self {
+            ToExchange::Bybit(client) => client.balance().await,
+            _ => Ok(None),
+        }
+    }
+
+    // 決済側の逆指値を設定する
+    // 非対応の取引所は何もしない
+    pub async fn stop_loss(
+        &self,
+        side: OrderSide,
+        qty: f64,
+        trigger_price: f64,
+        order_id: String,
+    ) -> Result<(), ExchangeError> {
+        match self {
+            ToExchange::Bybit(client) => client.stop_loss(side, qty, trigger_price, order_id).await,
+            _ => Ok(()),
+        }
+    }
+
+    // 接続断の際に取引所側で全注文をキャンセルさせる
+    // 非対応の取引所は何もしない
+    pub async fn arm_dead_man(&self, time_window_sec: u64) -> Result<(), ExchangeError> {
+        match self {
+            ToExchange::Bybit(client) => client.disconnect_cancel_all(time_window_sec).await,
+            _ => Ok(()),
+        }
+    }
+}
+
+// 取引所に残っている未約定注文
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OpenOrder {
+    // 自己指定の注文ID（Bybit: orderLinkId）
+    pub order_id: String,
+    pub side: OrderSide,
+    pub price: f64,
+    pub qty: f64,
+    // 未約定数量
+    pub leaves_qty: f64,
+}
+
+// 注文の約定状況
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Execution {
+    // 自己指定の注文ID（Bybit: orderLinkId）
+    pub order_id: String,
+    // 全約定・キャンセル・拒否のいずれかで取引所での処理が終了した
+    pub is_closed: bool,
+    pub cum_exec_qty: f64,
+    // 約定がない場合は0
+    pub average_price: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Instrument {
+    pub symbol: String,
+    pub ltp: f64,
+    pub volume24h: f64,
+    pub price_tick: f64,
+    pub size_tick: f64,
+    pub size_min: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Ticker {
+    pub symbol: String,
+    pub ltp: f64,
+    pub volume24h: f64,
+    pub best_ask: f64,
+    pub best_bid: f64,
+}
+
+impl Ticker {
+    pub fn new(symbol: String, ltp: f64, volume24h: f64, best_ask: f64, best_bid: f64) -> Self {
+        Ticker {
+            symbol,
+            ltp,
+            volume24h,
+            best_ask,
+            best_bid,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub enum DataType {
+    // default
+    #[default]
+    Snapshot,
+    UpdateDelta,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Orderboard {
+    pub symbol: String,
+    pub data_type: DataType,
+    pub a: Vec<Book>,
+    pub b: Vec<Book>,
+    pub t: Option<i64>,
+    pub u: Option<i64>,
+}
+
+impl Orderboard {
+    pub fn new(
+        data_type: DataType,
+        symbol: String,
+        a: Vec<Book>,
+        b: Vec<Book>,
+        t: Option<i64>,
+        u: Option<i64>,
+    ) -> Self {
+        Orderboard {
+            data_type,
+            symbol,
+            a,
+            b,
+            t,
+            u,
+        }
+    }
+}
+
+// 発注に使用できる残高
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Balance {
+    // 現物の場合は通貨ごとの残高で判定する
+    pub is_spot: bool,
+    // 証拠金として使用できる残高
+    pub available_margin: f64,
+    // 対象銘柄のレバレッジ、現物は1
+    pub leverage: f64,
+    // 現物の基軸通貨・決済通貨とそれぞれの使用できる残高
+    pub base_coin: String,
+    pub base_available: f64,
+    pub quote_coin: String,
+    pub quote_available: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Position {
+    pub symbol: String,
+    pub order_id: String,
+    pub side: String,
+    pub qty: f64,
+    pub price: f64,
+    pub pnl: f64,
+}
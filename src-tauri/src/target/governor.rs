@@ -0,0 +1,204 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use rand::Rng;
+use tokio::sync::Mutex;
+
+// 取引所APIのエンドポイント単位のレート制限を管理する
+// Bybitはレスポンスヘッダ X-Bapi-Limit / X-Bapi-Limit-Status / X-Bapi-Limit-Reset-Timestamp で残数を返す
+// ヘッダが取得できない場合は既定の上限値を1秒窓でローカルに計上する
+// why: 上限に達してから10006を受け取るのではなく、送信前に待機してエラー自体を回避する
+#[derive(Debug, Clone, Default)]
+pub struct Governor {
+    // 同一クライアントを共有する全タスクで同じ残数を参照する
+    limits: Arc<Mutex<HashMap<String, Limit>>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Limit {
+    // 窓あたりの上限回数
+    pub limit: u32,
+    // 窓内の残り回数
+    pub remaining: u32,
+    // 残数がリセットされる時刻
+    pub reset_at: DateTime<Utc>,
+}
+
+impl Limit {
+    pub fn new(limit: u32) -> Self {
+        Limit {
+            limit,
+            remaining: limit,
+            reset_at: Utc::now(),
+        }
+    }
+}
+
+impl Governor {
+    pub fn new(defaults: &[(&str, u32)]) -> Self {
+        let limits = defaults
+            .iter()
+            .map(|(endpoint, limit)| (endpoint.to_string(), Limit::new(*limit)))
+            .collect::<HashMap<String, Limit>>();
+
+        Governor {
+            limits: Arc::new(Mutex::new(limits)),
+        }
+    }
+
+    // Bybit v5 の既定上限（UID単位、1秒あたり）
+    pub fn bybit() -> Self {
+        Governor::new(&[
+            ("/v5/order/create", 10),
+            ("/v5/order/amend", 10),
+            ("/v5/order/cancel", 10),
+            ("/v5/order/cancel-all", 10),
+            ("/v5/order/realtime", 10),
+            ("/v5/order/history", 10),
+            ("/v5/position/list", 10),
+            ("/v5/position/trading-stop", 10),
+        ])
+    }
+
+    // 送信枠を1つ確保する
+    // 残数がなければリセット時刻まで待機する
+    // 未登録のエンドポイントは制限なしとして扱う
+    pub async fn acquire(&self, endpoint: &str) {
+        loop {
+            let wait = {
+                let mut w = self.limits.lock().await;
+                let limit = match w.get_mut(endpoint) {
+                    Some(v) => v,
+                    None => return,
+                };
+
+                let now = Utc::now();
+                if now >= limit.reset_at {
+                    limit.remaining = limit.limit;
+                    limit.reset_at = now + chrono::Duration::seconds(1);
+                }
+
+                if limit.remaining > 0 {
+                    limit.remaining -= 1;
+                    return;
+                }
+
+                (limit.reset_at - now).to_std().unwrap_or_default()
+            };
+
+            debug!("rate limit reached: {}, wait: {:?}", endpoint, wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    // レスポンスヘッダの値で残数を上書きする
+    // X-Bapi-Limit, X-Bapi-Limit-Status, X-Bapi-Limit-Reset-Timestamp(ms)
+    pub async fn observe(&self, endpoint: &str, limit: u32, remaining: u32, reset_at_ms: i64) {
+        let reset_at = DateTime::from_timestamp_millis(reset_at_ms).unwrap_or(Utc::now());
+
+        let mut w = self.limits.lock().await;
+        w.insert(
+            endpoint.to_string(),
+            Limit {
+                limit,
+                remaining,
+                reset_at,
+            },
+        );
+    }
+
+    // レート制限エラーを受けた場合、指定時間は送信を止める
+    pub async fn penalize(&self, endpoint: &str, wait: Duration) {
+        warn!("rate limit penalized: {}, wait: {:?}", endpoint, wait);
+
+        let mut w = self.limits.lock().await;
+        let limit = w
+            .entry(endpoint.to_string())
+            .or_insert_with(|| Limit::new(1));
+        limit.remaining = 0;
+        limit.reset_at = Utc::now() + chrono::Duration::from_std(wait).unwrap_or_default();
+    }
+
+    #[allow(unused)]
+    pub async fn remaining(&self, endpoint: &str) -> Option<u32> {
+        let r = self.limits.lock().await;
+        r.get(endpoint).map(|l| l.remaining)
+    }
+}
+
+// 再試行の待機時間を指数的に伸ばす
+// 複数タスクが同時に再送しないよう、待機時間を[1/2, 1]倍の範囲で揺らす
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    pub base_ms: u64,
+    pub max_ms: u64,
+    pub max_retry: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            base_ms: 200,
+            max_ms: 5_000,
+            max_retry: 3,
+        }
+    }
+}
+
+impl Backoff {
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exp = self
+            .base_ms
+            .saturating_mul(2u64.saturating_pow(attempt))
+            .min(self.max_ms);
+        let jittered = rand::rng().random_range(exp / 2..=exp);
+
+        Duration::from_millis(jittered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_delay_is_bounded() {
+        let backoff = Backoff {
+            base_ms: 100,
+            max_ms: 1_000,
+            max_retry: 5,
+        };
+
+        for attempt in 0..10 {
+            let exp = (100 * 2u64.pow(attempt)).min(1_000);
+            let delay = backoff.delay(attempt).as_millis() as u64;
+            assert!(
+                delay >= exp / 2 && delay <= exp,
+                "attempt: {}, delay: {}",
+                attempt,
+                delay
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_governor_consumes_and_penalizes() {
+        let governor = Governor::new(&[("/v5/order/create", 2)]);
+
+        governor.acquire("/v5/order/create").await;
+        governor.acquire("/v5/order/create").await;
+        assert_eq!(governor.remaining("/v5/order/create").await, Some(0));
+
+        // 未登録のエンドポイントは制限しない
+        governor.acquire("/v5/market/tickers").await;
+        assert_eq!(governor.remaining("/v5/market/tickers").await, None);
+
+        governor
+            .penalize("/v5/order/cancel", Duration::from_millis(50))
+            .await;
+        let start = std::time::Instant::now();
+        governor.acquire("/v5/order/cancel").await;
+        assert!(start.elapsed() >= Duration::from_millis(40));
+    }
+}
@@ -0,0 +1,6 @@
+pub mod exchange;
+pub mod governor;
+pub mod order;
+pub mod throttle;
+
+pub mod exchanges;
@@ -0,0 +1,862 @@
+use chrono::DateTime;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::target::exchanges::models::{OrderSide, OrderType, TimeInForce};
+use crate::target::throttle::Throttle;
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct Config {
+    pub symbol: String,
+
+    pub side: OrderSide,
+    pub size: f64,
+    pub is_post_only: bool,
+    // 指定があればis_post_onlyより優先する
+    #[serde(default)]
+    pub time_in_force: Option<TimeInForce>,
+    #[serde(default)]
+    pub order_type: OrderType,
+    #[serde(default)]
+    pub reduce_only: bool,
+    // 初回注文から指定秒数経過しても約定しない場合、壁の価格でIOC注文に切り替える、0で無効
+    // why: イベント前などに建玉を作り切る必要がある場合
+    #[serde(default)]
+    pub escalate_after_sec: i64,
+
+    pub tick_size: f64,
+    pub interval_sec: i64,
+    // 再注文間隔（ミリ秒）、0の場合はinterval_secを使用する
+    #[serde(default)]
+    pub interval_ms: u64,
+    // 間隔を空けずに連続で発注できる回数、0の場合は1
+    #[serde(default)]
+    pub burst: u32,
+    // 直近1分間の発注回数の上限、0で無効
+    #[serde(default)]
+    pub max_orders_per_minute: u32,
+
+    // 板に見せる1回あたりの注文数量、0で無効（sizeをそのまま注文する）
+    // why: 全数量を板に出すと、こちらの意図が他の参加者に見えてしまう
+    #[serde(default)]
+    pub clip_size: f64,
+    // clip_sizeを揺らす割合（0.2で±20%）
+    #[serde(default)]
+    pub clip_jitter: f64,
+    // 数量の最小単位、0で丸めない
+    #[serde(default)]
+    pub qty_step: f64,
+
+    // 追従する価格の距離の上限
+    #[serde(default)]
+    pub chase: ChaseGuard,
+    // 発注直前の価格の妥当性確認
+    #[serde(default)]
+    pub price_guard: PriceGuard,
+
+    // 建玉完成後に決済注文を出す場合の計画、未指定で完成時に終了する
+    #[serde(default)]
+    pub exit: Option<ExitPlan>,
+
+    // 上位の壁に分散して注文する場合の各壁への配分、空で無効
+    // 先頭が最良価格側の壁、要素数が対象とする壁の数となる
+    #[serde(default)]
+    pub ladder_weights: Vec<f64>,
+
+    // 板・Ticker・建玉の更新から注文を決める戦略
+    #[serde(default)]
+    pub strategy: StrategyKind,
+
+    // 壁に対する注文価格の位置、未指定は1tick前
+    #[serde(default)]
+    pub placement: Placement,
+
+    // 注文タスクの無応答を検知して注文をキャンセルするまでの秒数、0で無効
+    #[serde(default)]
+    pub heartbeat_timeout_sec: u64,
+    // 取引所側の切断時全キャンセル（Bybit DCP）の猶予秒数、0で無効
+    #[serde(default)]
+    pub dcp_window_sec: u64,
+
+    // 起動時に残っている前回の注文の扱い
+    #[serde(default)]
+    pub orphan: OrphanPolicy,
+}
+
+// 前回起動時の注文（自己注文IDが一致する未約定注文）の扱い
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum OrphanPolicy {
+    // 取引所上の注文をキャンセルし、新規に注文する
+    #[default]
+    #[serde(rename = "cancel")]
+    Cancel,
+    // 注文を引き継ぎ、次回の再注文時にキャンセルする
+    #[serde(rename = "adopt")]
+    Adopt,
+}
+
+// 注文を決める戦略の種類
+// 独自の戦略を追加する場合は種類を追加し、funcs::strategy::buildで生成する
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StrategyKind {
+    // 条件を満たす壁の前に注文し、壁の移動に追従する
+    #[default]
+    #[serde(rename = "wall_follow")]
+    WallFollow,
+}
+
+// 検出した壁に対する注文価格の置き方
+// 前: 買いは壁より高く、売りは壁より低い（約定しやすい側）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode")]
+pub enum Placement {
+    // 壁の前にticks分ずらす
+    #[serde(rename = "penny")]
+    Penny { ticks: u32 },
+    // 壁と同じ価格に並ぶ
+    #[serde(rename = "join")]
+    Join,
+    // 壁の後ろにticks分ずらす
+    #[serde(rename = "behind")]
+    Behind { ticks: u32 },
+    // 壁の価格に対するbps、正で前、負で後ろ
+    #[serde(rename = "bps")]
+    Bps { bps: f64 },
+}
+
+impl Default for Placement {
+    fn default() -> Self {
+        Placement::Penny { ticks: 1 }
+    }
+}
+
+// 追従先の価格が基準価格から離れすぎた場合に再注文を止める
+// why: 起動時に設定した価格範囲は、時間が経つと現在の相場から大きく離れていることがある
+// 各bpsは0で無効
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChaseGuard {
+    // 初回注文価格からの距離
+    #[serde(default)]
+    pub max_from_first_bps: f64,
+    // 最終約定価格（Ticker.ltp）からの距離
+    #[serde(default)]
+    pub max_from_ltp_bps: f64,
+    // 反対側の最良価格からの距離
+    #[serde(default)]
+    pub max_from_opposite_bps: f64,
+    #[serde(default)]
+    pub action: ChaseAction,
+}
+
+// 上限を超えた場合の動作
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChaseAction {
+    // 再注文を見送り、範囲内に戻れば再開する
+    #[default]
+    #[serde(rename = "pause")]
+    Pause,
+    // Runnerを停止する
+    #[serde(rename = "stop")]
+    Stop,
+}
+
+impl ChaseGuard {
+    // 上限を超えていれば理由を出力
+    // 基準価格が未取得（0以下）の場合は判定しない
+    pub fn check(
+        &self,
+        price: f64,
+        first_price: Option<f64>,
+        ltp: f64,
+        best_opposite: f64,
+    ) -> Option<String> {
+        let checks = [
+            (
+                "first order price",
+                first_price.unwrap_or_default(),
+                self.max_from_first_bps,
+            ),
+            ("ltp", ltp, self.max_from_ltp_bps),
+            (
+                "best opposite price",
+                best_opposite,
+                self.max_from_opposite_bps,
+            ),
+        ];
+
+        checks
+            .iter()
+            .filter(|(_, base, max_bps)| *base > 0.0 && *max_bps > 0.0)
+            .find_map(|(name, base, max_bps)| {
+                let bps = (price - base).abs() / base * 10_000.0;
+                (bps > *max_bps).then(|| {
+                    format!(
+                        "price: {} is {:.1}bps from {}: {}, max: {}bps",
+                        price, bps, name, base, max_bps
+                    )
+                })
+            })
+    }
+}
+
+// 発注直前の価格の妥当性確認
+// why: 価格範囲の設定ミスや壊れた板から、相場とかけ離れた価格で発注してしまう
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceGuard {
+    // 同じ側の最良価格及び最終約定価格からの距離の上限、0で無効
+    #[serde(default)]
+    pub max_deviation_bps: f64,
+    // 板に残る指値（GTC）が反対側の最良価格に届くことを許可する
+    #[serde(default)]
+    pub allow_cross: bool,
+}
+
+// 発注時点の最良価格及び最終約定価格
+// 未取得の値は0とし、判定に使用しない
+#[derive(Debug, Default, Clone)]
+pub struct Quote {
+    pub best_ask: f64,
+    pub best_bid: f64,
+    pub ltp: f64,
+}
+
+impl PriceGuard {
+    // 指値の価格をTicker及び板の最良価格と比較し、拒否する場合は理由を出力
+    // 成行注文は価格を持たないため判定しない
+    // IOC・FOK（期限経過後のIOCを含む）は約定させる注文のため、反対側に届く価格でも乖離のみ判定する
+    pub fn check(
+        &self,
+        side: &OrderSide,
+        price: f64,
+        order_type: &OrderType,
+        time_in_force: &TimeInForce,
+        quotes: &[(&str, Quote)],
+    ) -> Option<String> {
+        if *order_type == OrderType::Market {
+            return None;
+        }
+
+        for (source, quote) in quotes.iter() {
+            if !self.allow_cross && *time_in_force == TimeInForce::Gtc {
+                let is_cross = match side {
+                    OrderSide::Buy => quote.best_ask > 0.0 && price >= quote.best_ask,
+                    OrderSide::Sell => quote.best_bid > 0.0 && price <= quote.best_bid,
+                };
+                if is_cross {
+                    return Some(format!(
+                        "price: {} crosses {} best prices: [ask: {}, bid: {}]",
+                        price, source, quote.best_ask, quote.best_bid
+                    ));
+                }
+            }
+
+            if self.max_deviation_bps <= 0.0 {
+                continue;
+            }
+            let same_side = match side {
+                OrderSide::Buy => quote.best_bid,
+                OrderSide::Sell => quote.best_ask,
+            };
+            let reason = [("best price", same_side), ("ltp", quote.ltp)]
+                .iter()
+                .filter(|(_, base)| *base > 0.0)
+                .find_map(|(name, base)| {
+                    let bps = (price - base).abs() / base * 10_000.0;
+                    (bps > self.max_deviation_bps).then(|| {
+                        format!(
+                            "price: {} is {:.1}bps from {} {}: {}, max: {}bps",
+                            price, bps, source, name, base, self.max_deviation_bps
+                        )
+                    })
+                });
+            if reason.is_some() {
+                return reason;
+            }
+        }
+
+        None
+    }
+}
+
+// 建玉完成後の決済計画
+// 平均約定価格を基準に利確の指値と、任意で損切りの逆指値を設定する
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExitPlan {
+    pub take_profit: Distance,
+    #[serde(default)]
+    pub stop_loss: Option<Distance>,
+}
+
+// 基準価格からの距離
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "unit")]
+pub enum Distance {
+    #[serde(rename = "ticks")]
+    Ticks { value: u32 },
+    #[serde(rename = "bps")]
+    Bps { value: f64 },
+}
+
+impl Distance {
+    fn to_diff(&self, price: f64, tick_size: f64) -> f64 {
+        match self {
+            Distance::Ticks { value } => tick_size * *value as f64,
+            Distance::Bps { value } => price * value / 10_000.0,
+        }
+    }
+}
+
+impl ExitPlan {
+    // 建玉の方向と平均約定価格から、利確価格及び損切り価格を出力
+    pub fn prices(
+        &self,
+        entry_side: &OrderSide,
+        average_price: f64,
+        tick_size: f64,
+    ) -> (f64, Option<f64>) {
+        let direction = match entry_side {
+            OrderSide::Buy => 1.0,
+            OrderSide::Sell => -1.0,
+        };
+
+        let take_profit = round_to_tick(
+            average_price + direction * self.take_profit.to_diff(average_price, tick_size),
+            tick_size,
+        );
+        let stop_loss = self.stop_loss.as_ref().map(|d| {
+            round_to_tick(
+                average_price - direction * d.to_diff(average_price, tick_size),
+                tick_size,
+            )
+        });
+
+        (take_profit, stop_loss)
+    }
+}
+
+// 取引所の呼値に丸める
+fn round_to_tick(price: f64, tick_size: f64) -> f64 {
+    if tick_size > 0.0 {
+        (price / tick_size).round() * tick_size
+    } else {
+        price
+    }
+}
+
+impl Config {
+    #[allow(unused)]
+    pub fn new(symbol: String, size: f64, side: OrderSide) -> Self {
+        Config {
+            symbol,
+            side,
+            size,
+            is_post_only: true,
+            time_in_force: None,
+            order_type: OrderType::Limit,
+            reduce_only: false,
+            escalate_after_sec: 0,
+
+            tick_size: 0.01,
+
+            interval_sec: 5,
+            interval_ms: 0,
+            burst: 1,
+            max_orders_per_minute: 0,
+            clip_size: 0.0,
+            clip_jitter: 0.0,
+            qty_step: 0.0,
+            chase: ChaseGuard::default(),
+            price_guard: PriceGuard::default(),
+            exit: None,
+            ladder_weights: vec![],
+            strategy: StrategyKind::WallFollow,
+            placement: Placement::default(),
+
+            heartbeat_timeout_sec: 0,
+            dcp_window_sec: 0,
+
+            orphan: OrphanPolicy::Cancel,
+        }
+    }
+
+    pub fn to_order_info(&self) -> OrderInfo {
+        OrderInfo {
+            order_id: None,
+            price: None,
+            qty: self.size,
+            first_at: None,
+            working_qty: 0.0,
+            filled_qty: 0.0,
+            first_price: None,
+            hedged_qty: 0.0,
+            throttle: Throttle::new(self.interval_ms(), self.burst, self.max_orders_per_minute),
+            ..Default::default()
+        }
+    }
+
+    // 再注文間隔、interval_msの指定がなければinterval_secを使用する
+    pub fn interval_ms(&self) -> u64 {
+        if self.interval_ms > 0 {
+            self.interval_ms
+        } else {
+            self.interval_sec.max(0) as u64 * 1_000
+        }
+    }
+
+    pub fn time_in_force(&self) -> TimeInForce {
+        match self.time_in_force.clone() {
+            Some(v) => v,
+            None if self.is_post_only => TimeInForce::PostOnly,
+            None => TimeInForce::Gtc,
+        }
+    }
+
+    // 板に残らず即時に執行される注文か
+    pub fn is_immediate(&self) -> bool {
+        self.order_type == OrderType::Market
+            || matches!(self.time_in_force(), TimeInForce::Ioc | TimeInForce::Fok)
+    }
+
+    pub fn is_ok(&self) -> bool {
+        if self.symbol.is_empty() || self.size <= 0.0 {
+            return false;
+        }
+        // 負の比率、及び数量がqty_step未満となり消える段を含む分割は設定ミスとして扱う
+        if self.ladder_weights.iter().any(|w| *w < 0.0) {
+            return false;
+        }
+        let steps = self.ladder_weights.iter().filter(|w| **w > 0.0).count();
+        if steps > 1 && self.ladder().len() < steps {
+            return false;
+        }
+
+        true
+    }
+
+    // 決済注文の方向
+    pub fn exit_side(&self) -> OrderSide {
+        match self.side {
+            OrderSide::Buy => OrderSide::Sell,
+            OrderSide::Sell => OrderSide::Buy,
+        }
+    }
+
+    // 残数量から今回注文する数量を出力
+    // clip_sizeが有効であれば、clip_jitterの範囲で揺らした数量と残数量の小さい方
+    pub fn clip_qty(&self, remaining_qty: f64) -> f64 {
+        if self.clip_size <= 0.0 {
+            return remaining_qty;
+        }
+
+        let jitter = self.clip_jitter.clamp(0.0, 1.0);
+        let clip = if jitter > 0.0 {
+            self.clip_size * rand::rng().random_range(1.0 - jitter..=1.0 + jitter)
+        } else {
+            self.clip_size
+        };
+        let clip = if self.qty_step > 0.0 {
+            ((clip / self.qty_step + 1e-9).floor() * self.qty_step).max(self.qty_step)
+        } else {
+            clip
+        };
+
+        clip.min(remaining_qty)
+    }
+
+    // ladder_weightsに従って注文数量を分割した子注文の設定を出力
+    // 分割しない場合は自身のみ、端数は先頭の子注文に寄せ、数量が0の子注文は出力しない
+    pub fn ladder(&self) -> Vec<Config> {
+        let total_weight = self
+            .ladder_weights
+            .iter()
+            .filter(|w| **w > 0.0)
+            .sum::<f64>();
+        if self.ladder_weights.len() <= 1 || total_weight <= 0.0 {
+            let mut config = self.clone();
+            config.ladder_weights = vec![];
+            return vec![config];
+        }
+
+        let mut children = self
+            .ladder_weights
+            .iter()
+            .map(|w| {
+                let size = self.size * w.max(0.0) / total_weight;
+                let mut config = self.clone();
+                config.ladder_weights = vec![];
+                config.size = if self.qty_step > 0.0 {
+                    (size / self.qty_step + 1e-9).floor() * self.qty_step
+                } else {
+                    size
+                };
+                config
+            })
+            .collect::<Vec<Config>>();
+        let rest = self.size - children.iter().skip(1).map(|c| c.size).sum::<f64>();
+        children[0].size = rest;
+        // qty_stepへの切り捨てで数量が0になった子注文は除き、その分は先頭の子注文に寄せたままとする
+        children.retain(|c| c.size > 0.0);
+
+        children
+    }
+
+    // 対象の板の価格からplacementに従った注文価格を出力
+    pub fn placement_price(&self, wall_price: f64) -> f64 {
+        let direction = match self.side {
+            OrderSide::Buy => 1.0,
+            OrderSide::Sell => -1.0,
+        };
+
+        match self.placement {
+            Placement::Penny { ticks } => wall_price + direction * self.tick_size * ticks as f64,
+            Placement::Join => wall_price,
+            Placement::Behind { ticks } => wall_price - direction * self.tick_size * ticks as f64,
+            Placement::Bps { bps } => round_to_tick(
+                wall_price + direction * wall_price * bps / 10_000.0,
+                self.tick_size,
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct OrderInfo {
+    pub order_id: Option<String>,
+    pub price: Option<f64>,
+    pub qty: f64,
+    // 初回注文時間
+    #[serde(default)]
+    pub first_at: Option<DateTime<chrono::Utc>>,
+    // 板に出している注文の数量
+    #[serde(default)]
+    pub working_qty: f64,
+    // 現在の注文を出した時点の約定済み数量
+    #[serde(default)]
+    pub filled_qty: f64,
+    // 初回注文価格
+    #[serde(default)]
+    pub first_price: Option<f64>,
+    // ヘッジ済みの数量、ヘッジ先の単位
+    #[serde(default)]
+    pub hedged_qty: f64,
+    // 約定結果を読み戻せていないヘッジ注文の自己注文ID
+    #[serde(default)]
+    pub pending_hedge: Option<String>,
+    // 発注頻度の制限及び抑制回数
+    #[serde(default)]
+    pub throttle: Throttle,
+    // 側の累計約定数量及び約定代金、注文ごとの約定結果から集計する
+    #[serde(default)]
+    pub executed_qty: f64,
+    #[serde(default)]
+    pub executed_notional: f64,
+    // 現在の注文の集計済みの約定数量及び約定代金
+    #[serde(skip)]
+    order_executed: (f64, f64),
+}
+
+impl OrderInfo {
+    #[allow(unused)]
+    pub fn new() -> Self {
+        OrderInfo {
+            order_id: None,
+            price: None,
+            qty: 0.0,
+            first_at: None,
+            working_qty: 0.0,
+            filled_qty: 0.0,
+            first_price: None,
+            hedged_qty: 0.0,
+            pending_hedge: None,
+            throttle: Throttle::new(5_000, 1, 0),
+            executed_qty: 0.0,
+            executed_notional: 0.0,
+            order_executed: (0.0, 0.0),
+        }
+    }
+
+    pub fn set_order(&mut self, order_id: String, price: f64) {
+        let now = chrono::Utc::now();
+        self.order_id = Some(order_id);
+        self.price = Some(price);
+        self.order_executed = (0.0, 0.0);
+        if self.first_at.is_none() {
+            self.first_at = Some(now);
+            self.first_price = Some(price);
+        }
+    }
+
+    // 注文数量と注文時点の約定済み数量を記録する
+    pub fn set_progress(&mut self, working_qty: f64, filled_qty: f64) {
+        self.working_qty = working_qty;
+        self.filled_qty = filled_qty;
+    }
+
+    // 板に出している注文を取り下げた
+    // 注文IDは約定数量の集計に使用するため保持する
+    pub fn clear_working(&mut self) {
+        self.price = None;
+        self.working_qty = 0.0;
+    }
+
+    // 現在の注文の累計約定数量及び平均約定価格から、前回からの増分を側の累計に加える
+    // 増えた約定数量を返す
+    // why: 同じ自己注文IDで再注文するため、取引所の累計は注文ごとに0から数え直される
+    pub fn apply_execution(&mut self, cum_qty: f64, average_price: f64) -> f64 {
+        let (prev_qty, prev_notional) = self.order_executed;
+        if cum_qty <= prev_qty || average_price <= 0.0 {
+            return 0.0;
+        }
+
+        let notional = cum_qty * average_price;
+        self.executed_qty += cum_qty - prev_qty;
+        self.executed_notional += notional - prev_notional;
+        self.order_executed = (cum_qty, notional);
+        cum_qty - prev_qty
+    }
+
+    // 側の平均約定価格、約定がなければ0
+    pub fn average_price(&self) -> f64 {
+        if self.executed_qty > 0.0 {
+            self.executed_notional / self.executed_qty
+        } else {
+            0.0
+        }
+    }
+
+    // 板に出している注文が全て約定したか
+    pub fn is_working_filled(&self, filled_qty: f64) -> bool {
+        self.working_qty > 0.0 && filled_qty >= self.filled_qty + self.working_qty
+    }
+
+    // 初回注文からafter_sec以上経過しているか
+    // after_secが0以下であれば切り替えない
+    pub fn is_escalated(&self, after_sec: i64) -> bool {
+        if after_sec <= 0 {
+            return false;
+        }
+
+        match self.first_at {
+            Some(first) => {
+                chrono::Utc::now()
+                    .signed_duration_since(first)
+                    .num_seconds()
+                    >= after_sec
+            }
+            None => false,
+        }
+    }
+
+    // 既存の注文を引き継ぐ
+    // 引き継いだ時点では再注文の間隔制限をかけない
+    pub fn adopt(&mut self, order_id: String, price: f64) {
+        self.order_id = Some(order_id);
+        self.price = Some(price);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(side: OrderSide, placement: Placement) -> Config {
+        let mut config = Config::new("BTCUSDT".to_string(), 1.0, side);
+        config.tick_size = 0.5;
+        config.placement = placement;
+        config
+    }
+
+    #[test]
+    fn test_placement_price() {
+        let cases = vec![
+            (OrderSide::Buy, Placement::default(), 100.5),
+            (OrderSide::Sell, Placement::default(), 99.5),
+            (OrderSide::Buy, Placement::Penny { ticks: 3 }, 101.5),
+            (OrderSide::Buy, Placement::Join, 100.0),
+            (OrderSide::Sell, Placement::Join, 100.0),
+            (OrderSide::Buy, Placement::Behind { ticks: 2 }, 99.0),
+            (OrderSide::Sell, Placement::Behind { ticks: 2 }, 101.0),
+            // 100 * 30bps = 0.3 -> 呼値0.5に丸める
+            (OrderSide::Buy, Placement::Bps { bps: 30.0 }, 100.5),
+            (OrderSide::Sell, Placement::Bps { bps: -80.0 }, 101.0),
+        ];
+
+        for (side, placement, expected) in cases {
+            let c = config(side.clone(), placement.clone());
+            assert_eq!(
+                c.placement_price(100.0),
+                expected,
+                "side: {:?}, placement: {:?}",
+                side,
+                placement
+            );
+        }
+    }
+
+    #[test]
+    fn test_clip_qty() {
+        let mut c = config(OrderSide::Buy, Placement::default());
+        assert_eq!(c.clip_qty(3.0), 3.0);
+
+        c.clip_size = 1.0;
+        assert_eq!(c.clip_qty(3.0), 1.0);
+        assert_eq!(c.clip_qty(0.4), 0.4);
+
+        c.clip_jitter = 0.5;
+        c.qty_step = 0.1;
+        for _ in 0..100 {
+            let qty = c.clip_qty(10.0);
+            assert!((0.5..=1.5).contains(&qty), "qty: {}", qty);
+            assert!(
+                ((qty / 0.1).round() * 0.1 - qty).abs() < 1e-9,
+                "qty: {}",
+                qty
+            );
+        }
+    }
+
+    #[test]
+    fn test_ladder() {
+        let mut c = config(OrderSide::Buy, Placement::default());
+        c.size = 1.0;
+        assert_eq!(c.ladder().len(), 1);
+
+        c.ladder_weights = vec![2.0, 1.0, 1.0];
+        c.qty_step = 0.1;
+        let sizes = c.ladder().iter().map(|c| c.size).collect::<Vec<f64>>();
+        assert_eq!(sizes.len(), 3);
+        assert!((sizes[1] - 0.2).abs() < 1e-9, "sizes: {:?}", sizes);
+        assert!((sizes[2] - 0.2).abs() < 1e-9, "sizes: {:?}", sizes);
+        // 端数は先頭に寄せ、合計は元の数量と一致する
+        assert!((sizes.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        assert!(c.ladder().iter().all(|c| c.ladder_weights.is_empty()));
+        assert!(c.is_ok());
+
+        // qty_step未満に切り捨てられる段は出力せず、数量は先頭に寄せる
+        c.size = 0.3;
+        c.ladder_weights = vec![1.0, 1.0, 1.0, 1.0];
+        let sizes = c.ladder().iter().map(|c| c.size).collect::<Vec<f64>>();
+        assert!(sizes.iter().all(|s| *s > 0.0), "sizes: {:?}", sizes);
+        assert!((sizes.iter().sum::<f64>() - 0.3).abs() < 1e-9);
+        assert!(!c.is_ok());
+
+        c.ladder_weights = vec![1.0, -1.0];
+        assert!(!c.is_ok());
+    }
+
+    #[test]
+    fn test_apply_execution() {
+        let mut order = OrderInfo::new();
+        order.set_order("a".to_string(), 100.0);
+        assert_eq!(order.apply_execution(1.0, 100.0), 1.0);
+        // 同じ注文の累計は差分のみ加える
+        assert_eq!(order.apply_execution(2.0, 101.0), 1.0);
+        assert_eq!(order.apply_execution(2.0, 101.0), 0.0);
+
+        // 再注文後の累計は0から数え直される
+        order.set_order("a".to_string(), 99.0);
+        assert_eq!(order.apply_execution(1.0, 98.0), 1.0);
+        assert_eq!(order.executed_qty, 3.0);
+        assert!((order.average_price() - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_exit_prices() {
+        let plan = ExitPlan {
+            take_profit: Distance::Ticks { value: 4 },
+            stop_loss: Some(Distance::Bps { value: 100.0 }),
+        };
+
+        assert_eq!(
+            plan.prices(&OrderSide::Buy, 100.0, 0.5),
+            (102.0, Some(99.0))
+        );
+        assert_eq!(
+            plan.prices(&OrderSide::Sell, 100.0, 0.5),
+            (98.0, Some(101.0))
+        );
+
+        let plan = ExitPlan {
+            take_profit: Distance::Bps { value: 30.0 },
+            stop_loss: None,
+        };
+        assert_eq!(plan.prices(&OrderSide::Buy, 100.0, 0.5), (100.5, None));
+    }
+
+    #[test]
+    fn test_chase_guard() {
+        let guard = ChaseGuard {
+            max_from_first_bps: 100.0,
+            max_from_ltp_bps: 50.0,
+            max_from_opposite_bps: 0.0,
+            action: ChaseAction::Pause,
+        };
+
+        assert!(guard.check(100.4, Some(100.0), 100.0, 0.0).is_none());
+        // 初回注文価格から101bps
+        assert!(guard.check(98.99, Some(100.0), 99.0, 0.0).is_some());
+        // 最終約定価格から60bps
+        assert!(guard.check(100.6, None, 100.0, 0.0).is_some());
+        // 無効な上限及び未取得の基準価格は判定しない
+        assert!(guard.check(200.0, None, 0.0, 100.0).is_none());
+    }
+
+    #[test]
+    fn test_price_guard() {
+        let guard = PriceGuard {
+            max_deviation_bps: 100.0,
+            allow_cross: false,
+        };
+        let quotes = [
+            (
+                "ticker",
+                Quote {
+                    best_ask: 100.5,
+                    best_bid: 100.0,
+                    ltp: 100.0,
+                },
+            ),
+            (
+                "board",
+                Quote {
+                    best_ask: 100.5,
+                    best_bid: 0.0,
+                    ltp: 0.0,
+                },
+            ),
+        ];
+        let check = |side: OrderSide, price: f64, time_in_force: TimeInForce| {
+            guard.check(&side, price, &OrderType::Limit, &time_in_force, &quotes)
+        };
+
+        assert!(check(OrderSide::Buy, 100.0, TimeInForce::Gtc).is_none());
+        // ポストオンリーでなければ反対側の最良価格に届く指値は拒否する
+        assert!(check(OrderSide::Buy, 100.5, TimeInForce::Gtc).is_some());
+        assert!(check(OrderSide::Buy, 100.5, TimeInForce::PostOnly).is_none());
+        // 期限経過後のIOCは反対側に届いても乖離の範囲内であれば許可する
+        assert!(check(OrderSide::Buy, 100.6, TimeInForce::Ioc).is_none());
+        assert!(check(OrderSide::Sell, 99.9, TimeInForce::Fok).is_none());
+        assert!(check(OrderSide::Buy, 101.5, TimeInForce::Ioc).is_some());
+        // 同じ側の最良価格から150bps
+        assert!(check(OrderSide::Buy, 98.5, TimeInForce::Gtc).is_some());
+        assert!(check(OrderSide::Sell, 100.9, TimeInForce::Gtc).is_none());
+        // 成行注文は判定しない
+        assert!(guard
+            .check(
+                &OrderSide::Buy,
+                0.0,
+                &OrderType::Market,
+                &TimeInForce::Gtc,
+                &quotes
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_placement_deserialize() {
+        let p: Placement = serde_json::from_str(r#"{"mode":"behind","ticks":2}"#).unwrap();
+        assert_eq!(p, Placement::Behind { ticks: 2 });
+        let p: Placement = serde_json::from_str(r#"{"mode":"join"}"#).unwrap();
+        assert_eq!(p, Placement::Join);
+    }
+}
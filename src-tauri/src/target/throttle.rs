@@ -0,0 +1,126 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+// 発注頻度の制限
+// - トークンバケット: interval_msごとに1つ補充し、最大burst個まで連続で発注できる
+// - 直近1分間の発注回数がmax_per_minuteに達していれば発注しない
+// 時刻は呼び出し側から受け取り、単体で検証できるようにする
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Throttle {
+    pub interval_ms: u64,
+    pub burst: u32,
+    // 0で無効
+    pub max_per_minute: u32,
+    // 制限により発注しなかった回数
+    pub suppressed: u64,
+
+    #[serde(skip)]
+    tokens: f64,
+    #[serde(skip)]
+    refilled_at: Option<Instant>,
+    #[serde(skip)]
+    sent: VecDeque<Instant>,
+}
+
+impl Throttle {
+    pub fn new(interval_ms: u64, burst: u32, max_per_minute: u32) -> Self {
+        Throttle {
+            interval_ms,
+            burst: burst.max(1),
+            max_per_minute,
+            ..Default::default()
+        }
+    }
+
+    // 発注枠を1つ確保する
+    // 確保できない場合は抑制回数を加算する
+    pub fn try_acquire(&mut self, now: Instant) -> bool {
+        self.refill(now);
+        while let Some(at) = self.sent.front() {
+            if now.duration_since(*at) < MINUTE {
+                break;
+            }
+            self.sent.pop_front();
+        }
+
+        let is_over_minute =
+            self.max_per_minute > 0 && self.sent.len() >= self.max_per_minute as usize;
+        if self.tokens < 1.0 || is_over_minute {
+            self.suppressed += 1;
+            return false;
+        }
+
+        self.tokens -= 1.0;
+        if self.max_per_minute > 0 {
+            self.sent.push_back(now);
+        }
+        true
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let burst = self.burst.max(1) as f64;
+        self.tokens = match self.refilled_at {
+            // 初回は満たした状態から始める
+            None => burst,
+            Some(_) if self.interval_ms == 0 => burst,
+            Some(prev) => {
+                let elapsed_ms = now.duration_since(prev).as_secs_f64() * 1_000.0;
+                (self.tokens + elapsed_ms / self.interval_ms as f64).min(burst)
+            }
+        };
+        self.refilled_at = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_sub_second_interval() {
+        let mut throttle = Throttle::new(200, 1, 0);
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+
+        assert!(throttle.try_acquire(at(0)));
+        assert!(!throttle.try_acquire(at(100)));
+        assert!(throttle.try_acquire(at(200)));
+        assert!(!throttle.try_acquire(at(300)));
+        assert_eq!(throttle.suppressed, 2);
+    }
+
+    #[test]
+    fn test_throttle_burst() {
+        let mut throttle = Throttle::new(1_000, 3, 0);
+        let start = Instant::now();
+
+        assert!(throttle.try_acquire(start));
+        assert!(throttle.try_acquire(start));
+        assert!(throttle.try_acquire(start));
+        assert!(!throttle.try_acquire(start));
+        // 500msで半分のみ補充される
+        assert!(!throttle.try_acquire(start + Duration::from_millis(500)));
+        assert!(throttle.try_acquire(start + Duration::from_millis(1_000)));
+    }
+
+    #[test]
+    fn test_throttle_per_minute() {
+        let mut throttle = Throttle::new(0, 1, 3);
+        let start = Instant::now();
+        let at = |sec: u64| start + Duration::from_secs(sec);
+
+        assert!(throttle.try_acquire(at(0)));
+        assert!(throttle.try_acquire(at(10)));
+        assert!(throttle.try_acquire(at(20)));
+        assert!(!throttle.try_acquire(at(30)));
+        // 最初の発注から1分経過で枠が空く
+        assert!(throttle.try_acquire(at(60)));
+        assert_eq!(throttle.suppressed, 1);
+    }
+}
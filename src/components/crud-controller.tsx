@@ -1,9 +1,9 @@
 import { invoke } from "@tauri-apps/api/core";
-import { Controller } from "./models";
+import { Controller, KillResponse, PnlSummary, RunnerItem, StopResponse } from "./models";
 
-export const startController = async (): Promise<Controller> => {
+export const startController = async (runnerId: string): Promise<Controller> => {
     try {
-        let res = await invoke('start_controller', {});
+        let res = await invoke('start_controller', { runner_id: runnerId });
         const controller = res as Controller;
         return controller;
     } catch (e) {
@@ -12,10 +12,23 @@ export const startController = async (): Promise<Controller> => {
     }
 };
 
-export const stopController = async (): Promise<Controller> => {
+export const stopController = async (runnerId: string): Promise<StopResponse> => {
     try {
-        let res = await invoke('stop_controller', {});
-        const controller = res as Controller;
+        let res = await invoke('stop_controller', { runner_id: runnerId });
+        const controller = res as StopResponse;
+        return controller;
+    } catch (e) {
+        console.error(e);
+        throw e;
+    }
+};
+
+// 全停止、flattenの場合は建玉も成行で決済する
+// runnerIdを省略した場合は全てのRunnerを停止する
+export const killSwitch = async (flatten: boolean, runnerId?: string): Promise<KillResponse> => {
+    try {
+        let res = await invoke('kill_switch', { runner_id: runnerId ?? null, flatten: flatten });
+        const controller = res as KillResponse;
         return controller;
     } catch (e) {
         console.error(e);
@@ -23,6 +36,28 @@ export const stopController = async (): Promise<Controller> => {
     }
 };
 
+// 起動中の損益及び手数料
+export const getPnl = async (runnerId: string): Promise<PnlSummary> => {
+    try {
+        let res = await invoke('get_pnl', { runner_id: runnerId });
+        return res as PnlSummary;
+    } catch (e) {
+        console.error(e);
+        throw e;
+    }
+};
+
+// 登録済みのRunnerの一覧
+export const listRunners = async (): Promise<RunnerItem[]> => {
+    try {
+        let res = await invoke('list_runners', {});
+        return res as RunnerItem[];
+    } catch (e) {
+        console.error(e);
+        throw e;
+    }
+};
+
 
 export const postController = async () => {
     try {
@@ -54,7 +89,7 @@ export const postController = async () => {
         };
 
 
-        let res = await invoke('post_controller', { 'value': controller });
+        let res = await invoke('post_controller', { 'runner_id': 'default', 'value': controller });
 
         console.log(res);
     } catch (e) {
@@ -64,7 +99,7 @@ export const postController = async () => {
 
 export const getController = async () => {
     try {
-        let res = await invoke('get_controller', {});
+        let res = await invoke('get_controller', { 'runner_id': 'default' });
 
         console.log(res);
     } catch (e) {
@@ -102,7 +137,7 @@ export const putController = async () => {
         };
 
 
-        let res = await invoke('put_controller', { 'value': controller });
+        let res = await invoke('put_controller', { 'runner_id': 'default', 'value': controller });
 
         console.log(res);
     } catch (e) {
@@ -112,7 +147,7 @@ export const putController = async () => {
 
 export const deleteController = async () => {
     try {
-        let res = await invoke('delete_controller', {});
+        let res = await invoke('delete_controller', { 'runner_id': 'default' });
 
         console.log(res);
     } catch (e) {
@@ -2,9 +2,9 @@ import { Form, Input, Button, InputNumber, Select, FormProps, Switch, message, S
 import { Board, Controller, Exchange, Order, SupportedExchanges, SupportedBookSides, SupportedOrderSides, Ticker } from "./models";
 import { invoke } from "@tauri-apps/api/core";
 import { useEffect, useState } from "react";
-import { startController, stopController } from "./crud-controller";
+import { killSwitch, startController, stopController } from "./crud-controller";
 
-import { CaretRightOutlined, PauseOutlined } from '@ant-design/icons';
+import { CaretRightOutlined, PauseOutlined, StopOutlined } from '@ant-design/icons';
 
 import { History } from "./history";
 import { Logger } from "./logger";
@@ -58,6 +58,8 @@ export const FormComponent = () => {
     const [language, setLanguage] = useState('ja');
 
     const [controller, setController] = useState<Controller>(defaultController);
+    // 操作対象のRunner
+    const [runnerId, setRunnerId] = useState('default');
     const [form] = Form.useForm<Controller>();
     const [instruments, setInstruments] = useState<Instrument[]>([]);
     const [selectInstrument, setSelectInstrument] = useState<Instrument | null>(null);
@@ -98,7 +100,7 @@ export const FormComponent = () => {
     const start = async () => {
         setLoading(true);
         try {
-            const controller = await startController();
+            const controller = await startController(runnerId);
             setController(controller);
             setHistory([...history, controller]);
             form.setFieldValue('is_running', controller.is_running);
@@ -123,7 +125,7 @@ export const FormComponent = () => {
     const stop = async () => {
         setLoading(true);
         try {
-            const controller = await stopController();
+            const controller = await stopController(runnerId);
             setController(controller);
             form.setFieldValue('is_running', controller.is_running);
             message.open({
@@ -144,13 +146,61 @@ export const FormComponent = () => {
         }
     };
 
+    // 全停止、Shiftを押しながらの場合は建玉も決済する
+    const kill = async (flatten: boolean) => {
+        setLoading(true);
+        try {
+            const res = await killSwitch(flatten);
+            setController((prev) => ({ ...prev, is_running: false }));
+            form.setFieldValue('is_running', false);
+            const cancelled = res.results.reduce((sum, r) => sum + r.cancelled.length, 0);
+            const closed = res.results.reduce((sum, r) => sum + r.closed.length, 0);
+            const errors = res.results.flatMap((r) => r.errors);
+            message.open({
+                type: errors.length > 0 ? 'error' : 'success',
+                duration: 5,
+                content: `Kill switch: stopped ${res.runner_ids.length} runners, cancelled ${cancelled} orders, closed ${closed} positions${errors.length > 0 ? `, errors: ${errors.join(', ')}` : ''}`
+            });
+        } catch (error: any) {
+            console.error(error);
+            message.open({
+                type: 'error',
+                duration: 3,
+                content: `${error.msg}, cause: ${error.cause}`
+            });
+        } finally {
+            setLoading(false);
+        }
+    };
+
+    // Ctrl+Shift+K: 全停止、Ctrl+Shift+L: 全停止及び建玉の決済
+    useEffect(() => {
+        const onKeyDown = (e: KeyboardEvent) => {
+            if (!e.ctrlKey || !e.shiftKey) {
+                return;
+            }
+            switch (e.key.toLowerCase()) {
+                case 'k':
+                    e.preventDefault();
+                    kill(false);
+                    break;
+                case 'l':
+                    e.preventDefault();
+                    kill(true);
+                    break;
+            }
+        };
+        window.addEventListener('keydown', onKeyDown);
+        return () => window.removeEventListener('keydown', onKeyDown);
+    }, []);
+
 
     const onFinish: FormProps<Controller>['onFinish'] = async (values: Controller) => {
         setLoading(true);
         try {
             values.order.tick_size = selectInstrument?.price_tick || 0;
 
-            const res = await invoke('post_controller', { value: values });
+            const res = await invoke('post_controller', { runner_id: runnerId, value: values });
             console.log(res);
             message.open({
                 type: 'success',
@@ -235,6 +285,13 @@ export const FormComponent = () => {
                 {
                     controller.is_running ? <FloatButton icon={<PauseOutlined />} onClick={stop} /> : <FloatButton icon={<CaretRightOutlined />} onClick={start} />
                 }
+                <FloatButton
+                    icon={<StopOutlined />}
+                    type="primary"
+                    style={{ insetInlineEnd: 80 }}
+                    tooltip={t('button.kill')}
+                    onClick={(e) => kill(e.shiftKey)}
+                />
 
                 {/* 言語切替 */}
                 {/* 上部左端固定 */}
@@ -246,7 +303,7 @@ export const FormComponent = () => {
                     >
                         {language === 'en' ? '日本語へ言語変更' : 'Switch to English'}
                     </Button>
-                    <Logger />
+                    <Logger runnerId={runnerId} />
                     <History controllers={history} onSelectController={onSelectController} />
                 </Flex>
 
@@ -262,6 +319,13 @@ export const FormComponent = () => {
                     onValuesChange={onChangeForm}
                 >
 
+                    <Form.Item
+                        label={t('runner.label')}
+                        tooltip={t('runner.description')}
+                    >
+                        <Input value={runnerId} onChange={(e) => setRunnerId(e.target.value)} disabled={controller.is_running} />
+                    </Form.Item>
+
                     <Form.Item<Controller>
                         label={t('running.label')}
                         tooltip={t('running.description')}
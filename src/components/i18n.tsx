@@ -4,6 +4,10 @@ import { initReactI18next } from 'react-i18next';
 const resources = {
     en: {
         translation: {
+            "runner": {
+                "label": "Runner ID",
+                "description": "Identifies the runner. Runners with different IDs run concurrently."
+            },
             "running": {
                 "label": "Running",
                 "description": "Indicates whether the program is actively tracking limit orders."
@@ -66,12 +70,17 @@ const resources = {
                     "description": "Saves the configuration. When the settings are overwritten, monitoring will be stopped, orders will be cancelled, and monitoring will resume with the new settings."
                 },
                 "start": "Start",
-                "stop": "Stop"
+                "stop": "Stop",
+                "kill": "Kill switch: cancel all orders (Ctrl+Shift+K). Shift+click or Ctrl+Shift+L also closes positions."
             }
         }
     },
     ja: {
         translation: {
+            "runner": {
+                "label": "Runner ID",
+                "description": "Runnerを識別します。異なるIDのRunnerは同時に実行できます。"
+            },
             "running": {
                 "label": "実行中",
                 "description": "プログラムが指値注文追従を実行中かどうかを示します。"
@@ -134,7 +143,8 @@ const resources = {
                     "description": "設定を保存します。設定が上書きされるとき、監視情報を停止・注文をキャンセルし、新しい設定で再度監視を処理を再開します。"
                 },
                 "start": "開始",
-                "stop": "停止"
+                "stop": "停止",
+                "kill": "全停止: 全注文をキャンセルします (Ctrl+Shift+K)。Shift+クリックまたはCtrl+Shift+Lで建玉も決済します。"
             }
         }
     }
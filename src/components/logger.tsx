@@ -8,7 +8,7 @@ interface Log {
     timestamp: string,
 };
 
-const Logger = () => {
+const Logger = ({ runnerId }: { runnerId: string }) => {
     const [open, setOpen] = useState(false);
     const [logger, setLogger] = useState<Log[]>([]);
     const [loading, setLoading] = useState(false);
@@ -16,7 +16,7 @@ const Logger = () => {
     const fetchLogger = async () => {
         setLoading(true);
         try {
-            const response = await invoke('get_logger', {});
+            const response = await invoke('get_logger', { runner_id: runnerId });
             const data = response as Log[];
             console.log(data);
 
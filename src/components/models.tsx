@@ -14,8 +14,91 @@ export interface Controller {
     exchange: Exchange;
     board: Board;
     order: Order;
+    opposite?: Leg;
+    schedule?: Schedule;
+    hedge?: Hedge;
+    breaker?: Breaker;
+    stale?: Stale;
+    risk?: RiskLimits;
+    fee?: Fee;
 }
 
+// 損益の集計に使用する手数料率（bps）、メイカーのリベートは負の値
+export interface Fee {
+    maker_bps?: number;
+    taker_bps?: number;
+}
+
+// 起動中の損益及び手数料
+export interface PnlSummary {
+    symbol: string;
+    net_position: number;
+    average_price: number;
+    realized_pnl: number;
+    unrealized_pnl: number;
+    ltp: number;
+    fees: number;
+    net_pnl: number;
+    maker_volume: number;
+    taker_volume: number;
+    fills: number;
+}
+
+// 発注前の上限、各上限は0で無効
+export interface RiskLimits {
+    max_position?: number;
+    max_order_notional?: number;
+    max_open_orders?: number;
+    max_daily_loss?: number;
+    max_account_daily_loss?: number;
+}
+
+// 急変時に注文を取り下げて一時停止する、各上限は0で無効
+export interface Breaker {
+    window_sec: number;
+    max_volatility_bps?: number;
+    max_change_bps?: number;
+    cooldown_sec: number;
+}
+
+// 板またはTickerの更新が途絶えた場合に注文を取り下げる、各上限は0で無効
+export interface Stale {
+    book_timeout_ms?: number;
+    ticker_timeout_ms?: number;
+}
+
+// 約定時に別の取引所または銘柄で反対売買する
+export interface Hedge {
+    exchange: Exchange;
+    symbol: string;
+    ratio: number;
+    order_type?: 'limit' | 'market';
+    qty_step?: number;
+}
+
+// 両側で追従する場合の反対側
+export interface Leg {
+    board: Board;
+    size: number;
+}
+
+// 取引時間帯、時刻は "HH:MM:SS"
+export interface Schedule {
+    timezone: string;
+    windows: ScheduleWindow[];
+    blackouts?: Blackout[];
+}
+
+export interface ScheduleWindow {
+    weekday: 'Mon' | 'Tue' | 'Wed' | 'Thu' | 'Fri' | 'Sat' | 'Sun';
+    start: string;
+    end: string;
+}
+
+export type Blackout =
+    | { kind: 'period'; start: string; end: string }
+    | { kind: 'daily'; at: string; before_sec: number; after_sec: number };
+
 export interface Exchange {
     name: ExchangeName;
     key: string;
@@ -38,16 +121,131 @@ export interface Order {
     side: OrderSide;
     size: number;
     is_post_only: boolean;
+    time_in_force?: 'gtc' | 'post_only' | 'ioc' | 'fok';
+    order_type?: 'limit' | 'market';
+    reduce_only?: boolean;
+    escalate_after_sec?: number;
 
     tick_size: number;
     interval_sec: number;
+    interval_ms?: number;
+    burst?: number;
+    max_orders_per_minute?: number;
+    clip_size?: number;
+    clip_jitter?: number;
+    qty_step?: number;
+    chase?: ChaseGuard;
+    price_guard?: PriceGuard;
+    exit?: ExitPlan;
+    ladder_weights?: number[];
+    placement?: Placement;
+    strategy?: 'wall_follow';
+
+    heartbeat_timeout_sec?: number;
+    dcp_window_sec?: number;
+
+    orphan?: 'cancel' | 'adopt';
 }
 
 
+// 追従距離の上限、各bpsは0で無効
+export interface ChaseGuard {
+    max_from_first_bps?: number;
+    max_from_ltp_bps?: number;
+    max_from_opposite_bps?: number;
+    action?: 'pause' | 'stop';
+}
+
+// 発注直前の価格確認、max_deviation_bpsは0で無効
+export interface PriceGuard {
+    max_deviation_bps?: number;
+    allow_cross?: boolean;
+}
+
+export type Distance =
+    | { unit: 'ticks'; value: number }
+    | { unit: 'bps'; value: number };
+
+export interface ExitPlan {
+    take_profit: Distance;
+    stop_loss?: Distance;
+}
+
+export type Placement =
+    | { mode: 'penny'; ticks: number }
+    | { mode: 'join' }
+    | { mode: 'behind'; ticks: number }
+    | { mode: 'bps'; bps: number };
+
 export interface Ticker {
     symbol: string;
     ltp: number;
     volume24h: number;
     best_ask: number;
     best_bid: number;
-}
\ No newline at end of file
+}
+
+export interface OrderInfo {
+    order_id?: string;
+    price?: number;
+    qty: number;
+    first_at?: string;
+    working_qty: number;
+    filled_qty: number;
+    first_price?: number;
+    hedged_qty: number;
+    pending_hedge?: string;
+    throttle: Throttle;
+    executed_qty: number;
+    executed_notional: number;
+}
+
+// 発注頻度の制限、suppressedは制限により発注しなかった回数
+export interface Throttle {
+    interval_ms: number;
+    burst: number;
+    max_per_minute: number;
+    suppressed: number;
+}
+
+export interface Position {
+    symbol: string;
+    order_id: string;
+    side: string;
+    qty: number;
+    price: number;
+    pnl: number;
+}
+
+// 全停止の銘柄ごとの結果
+export interface KillResult {
+    exchange: string;
+    symbol: string;
+    cancelled: string[];
+    closed: { side: OrderSide; qty: number }[];
+    errors: string[];
+}
+
+// 全停止の結果
+export interface KillResponse {
+    runner_ids: string[];
+    results: KillResult[];
+}
+
+// Runnerの状態
+export type RunnerStatus = 'stopped' | 'starting' | 'running' | 'finished';
+
+// 登録済みのRunner
+export interface RunnerItem {
+    runner_id: string;
+    status: RunnerStatus;
+    controller: Controller;
+    pnl?: PnlSummary;
+}
+
+// 停止時の最終状態（Controllerの値を含む）
+export interface StopResponse extends Controller {
+    orders: OrderInfo[];
+    positions: Position[];
+    pnl?: PnlSummary;
+}